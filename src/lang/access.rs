@@ -1,23 +1,51 @@
-use super::data_type::DataType;
+use std::rc::Rc;
+use std::cell::RefCell;
+use super::data_type::{DataType, UserData};
 use super::error::RuntimeError;
 
+// start, end and step, a part left off is None
+pub type SliceBounds = (Option<i64>, Option<i64>, Option<i64>);
+
+// a negative index counts back from the end
+fn from_end(index: i64, length: usize) -> Option<usize> {
+    let index = if index < 0 { index + length as i64 } else { index };
+    if index < 0 || index as usize >= length {
+        return None;
+    }
+    Some(index as usize)
+}
+
 pub fn get_item_in_collection(
     accessor: DataType,
     target: DataType,
 ) -> Result<DataType, RuntimeError> {
+    if target.is_dict() {
+        let key = accessor.to_dict_key()?;
+        let dict = target.get_dict()?;
+        let dict = dict.borrow();
+        return match dict.get(&key) {
+            Some(item) => Ok(item.clone()),
+            None => Err(RuntimeError::KeyNotInDict),
+        };
+    }
+    if let DataType::UserData(ref user_data) = target {
+        return get_method(&accessor, user_data);
+    }
     if accessor.is_int() {
-        let index = accessor.as_int() as usize;
+        let index = accessor.as_int();
         if target.is_string() {
             let string = target.get_string()?;
             let string = string.borrow();
-            if let Some(c) = string.chars().nth(index) {
-                return Ok(DataType::Char(c));
+            if let Some(index) = from_end(index, string.chars().count()) {
+                if let Some(c) = string.chars().nth(index) {
+                    return Ok(DataType::Char(c));
+                }
             }
         } else if target.is_array() {
             let array = target.get_array()?;
             let array = array.borrow();
-            if let Some(item) = array.get(index) {
-                return Ok(item.clone());
+            if let Some(index) = from_end(index, array.len()) {
+                return Ok(array[index].clone());
             }
         }
         return Err(RuntimeError::CannotAccessWithAccessor);
@@ -25,27 +53,53 @@ pub fn get_item_in_collection(
     Err(RuntimeError::InvalidAccessor)
 }
 
+// methods of host values are found by name
+fn get_method(accessor: &DataType, user_data: &Rc<UserData>) -> Result<DataType, RuntimeError> {
+    if !accessor.is_string() {
+        return Err(RuntimeError::InvalidAccessor);
+    }
+    let name = accessor.as_string().borrow().clone();
+    match UserData::bound_method(user_data, &name) {
+        Some(method) => Ok(DataType::NativeFunction(Rc::new(method))),
+        None => Err(RuntimeError::UnknownMethod(user_data.type_name, name)),
+    }
+}
+
 pub fn update_elememnt_in_collection(
     accessor: DataType,
     target: DataType,
     value: DataType,
 ) -> Result<(), RuntimeError> {
+    if target.is_dict() {
+        let key = accessor.to_dict_key()?;
+        let dict = target.get_dict()?;
+        dict.borrow_mut().insert(key, value);
+        return Ok(());
+    }
     if accessor.is_int() {
-        let index = accessor.as_int() as usize;
+        let index = accessor.as_int();
         if target.is_string() {
             if !value.is_char() {
                 return Err(RuntimeError::CannotInsertToString);
             }
             let string = target.get_string()?;
             let mut string = string.borrow_mut();
-            string.remove(index);
-            string.insert(index, value.as_char());
+            let index = match from_end(index, string.chars().count()) {
+                Some(index) => index,
+                None => return Err(RuntimeError::IndexOutOfBound),
+            };
+            // the index counts chars not bytes
+            let (start, old) = match string.char_indices().nth(index) {
+                Some(found) => found,
+                None => return Err(RuntimeError::IndexOutOfBound),
+            };
+            string.replace_range(start..start + old.len_utf8(), &value.as_char().to_string());
             return Ok(());
         } else if target.is_array() {
             let array = target.get_array()?;
             let mut array = array.borrow_mut();
-            if let Some(item) = array.get_mut(index) {
-                *item = value;
+            if let Some(index) = from_end(index, array.len()) {
+                array[index] = value;
                 return Ok(());
             }
         }
@@ -53,3 +107,265 @@ pub fn update_elememnt_in_collection(
     }
     Err(RuntimeError::InvalidAccessor)
 }
+
+// the start, end and step of a slice, ends past the collection are cut to it
+fn slice_range(bounds: SliceBounds, length: usize) -> Result<(i64, i64, i64), RuntimeError> {
+    let (start, end, step) = bounds;
+    let step = step.unwrap_or(1);
+    if step == 0 {
+        return Err(RuntimeError::SliceStepZero);
+    }
+    let length = length as i64;
+    // a negative step runs from the back, so it can end before the first item
+    let (lowest, highest) = if step > 0 { (0, length) } else { (-1, length - 1) };
+    let clamp = |index: i64| {
+        let index = if index < 0 { index + length } else { index };
+        index.max(lowest).min(highest)
+    };
+    let start = start.map_or(if step > 0 { lowest } else { highest }, clamp);
+    let end = end.map_or(if step > 0 { highest } else { lowest }, clamp);
+    Ok((start, end, step))
+}
+
+// the indexes a slice takes in order
+fn slice_indexes(bounds: SliceBounds, length: usize) -> Result<Vec<usize>, RuntimeError> {
+    let (start, end, step) = slice_range(bounds, length)?;
+    let mut indexes = Vec::new();
+    let mut index = start;
+    while (step > 0 && index < end) || (step < 0 && index > end) {
+        indexes.push(index as usize);
+        // a step past the largest integer also leaves the slice
+        index = match index.checked_add(step) {
+            Some(index) if index >= 0 && index < length as i64 => index,
+            _ => break,
+        };
+    }
+    Ok(indexes)
+}
+
+pub fn get_slice(bounds: SliceBounds, target: DataType) -> Result<DataType, RuntimeError> {
+    if target.is_string() {
+        let chars: Vec<char> = target.get_string()?.borrow().chars().collect();
+        let sliced = slice_indexes(bounds, chars.len())?
+            .into_iter()
+            .map(|index| chars[index])
+            .collect();
+        return Ok(DataType::String(Rc::new(RefCell::new(sliced))));
+    }
+    if target.is_array() {
+        let items = target.get_array()?.borrow();
+        let sliced = slice_indexes(bounds, items.len())?
+            .into_iter()
+            .map(|index| items[index].clone())
+            .collect();
+        return Ok(DataType::Array(Rc::new(RefCell::new(sliced))));
+    }
+    Err(RuntimeError::CannotAccessWithAccessor)
+}
+
+// a slice with a step of 1 is swapped for any number of items,
+// any other step needs one item for each index it takes
+pub fn update_slice(bounds: SliceBounds, target: DataType, value: DataType) -> Result<(), RuntimeError> {
+    if target.is_string() {
+        let new_chars = to_chars(&value)?;
+        return edit_chars(&target, |chars| splice(chars, bounds, new_chars));
+    }
+    if target.is_array() {
+        // cloned first as the value can be the target
+        let new_items = value.get_array()?.borrow().clone();
+        let array = target.get_array()?;
+        splice(&mut array.borrow_mut(), bounds, new_items)?;
+        return Ok(());
+    }
+    Err(RuntimeError::CannotAccessWithAccessor)
+}
+
+fn splice<T>(items: &mut Vec<T>, bounds: SliceBounds, new_items: Vec<T>) -> Result<(), RuntimeError> {
+    let (start, end, step) = slice_range(bounds, items.len())?;
+    if step == 1 {
+        items.splice(start as usize..end.max(start) as usize, new_items);
+        return Ok(());
+    }
+    let indexes = slice_indexes(bounds, items.len())?;
+    if indexes.len() != new_items.len() {
+        return Err(RuntimeError::ShapeMismatch(indexes.len(), new_items.len()));
+    }
+    for (index, item) in indexes.into_iter().zip(new_items) {
+        items[index] = item;
+    }
+    Ok(())
+}
+
+pub fn has_key_in_collection(accessor: DataType, target: DataType) -> Result<bool, RuntimeError> {
+    let key = accessor.to_dict_key()?;
+    let dict = target.get_dict()?;
+    let has_key = dict.borrow().contains_key(&key);
+    Ok(has_key)
+}
+
+pub fn remove_key_from_collection(
+    accessor: DataType,
+    target: DataType,
+) -> Result<DataType, RuntimeError> {
+    if target.is_array() || target.is_string() {
+        return remove_at(accessor, &target);
+    }
+    let key = accessor.to_dict_key()?;
+    let dict = target.get_dict()?;
+    let removed = dict.borrow_mut().remove(&key);
+    match removed {
+        Some(item) => Ok(item),
+        None => Err(RuntimeError::KeyNotInDict),
+    }
+}
+
+// a char or the chars of a string to add to a string
+fn to_chars(value: &DataType) -> Result<Vec<char>, RuntimeError> {
+    if value.is_char() {
+        return Ok(vec![value.as_char()]);
+    }
+    if value.is_string() {
+        return Ok(value.get_string()?.borrow().chars().collect());
+    }
+    Err(RuntimeError::CannotInsertToString)
+}
+
+// strings are edited as chars so indexes count chars not bytes
+fn edit_chars<R>(
+    target: &DataType,
+    edit: impl FnOnce(&mut Vec<char>) -> Result<R, RuntimeError>,
+) -> Result<R, RuntimeError> {
+    let string = target.get_string()?;
+    let mut chars: Vec<char> = string.borrow().chars().collect();
+    let result = edit(&mut chars)?;
+    *string.borrow_mut() = chars.into_iter().collect();
+    Ok(result)
+}
+
+fn remove_at(accessor: DataType, target: &DataType) -> Result<DataType, RuntimeError> {
+    if !accessor.is_int() {
+        return Err(RuntimeError::InvalidAccessor);
+    }
+    let index = accessor.as_int();
+    if target.is_string() {
+        return edit_chars(target, |chars| match from_end(index, chars.len()) {
+            Some(index) => Ok(DataType::Char(chars.remove(index))),
+            None => Err(RuntimeError::IndexOutOfBound),
+        });
+    }
+    let mut array = target.get_array()?.borrow_mut();
+    match from_end(index, array.len()) {
+        Some(index) => Ok(array.remove(index)),
+        None => Err(RuntimeError::IndexOutOfBound),
+    }
+}
+
+// the new item goes before the index, the length adds it to the end
+pub fn insert_in_collection(
+    accessor: DataType,
+    target: DataType,
+    value: DataType,
+) -> Result<(), RuntimeError> {
+    if !accessor.is_int() {
+        return Err(RuntimeError::InvalidAccessor);
+    }
+    let index = accessor.as_int();
+    let insert_index = |length: usize| {
+        let index = if index < 0 { index + length as i64 } else { index };
+        if index < 0 || index as usize > length {
+            return Err(RuntimeError::IndexOutOfBound);
+        }
+        Ok(index as usize)
+    };
+    if target.is_string() {
+        let new_chars = to_chars(&value)?;
+        return edit_chars(&target, |chars| {
+            let index = insert_index(chars.len())?;
+            chars.splice(index..index, new_chars);
+            Ok(())
+        });
+    }
+    let mut array = target.get_array()?.borrow_mut();
+    let index = insert_index(array.len())?;
+    array.insert(index, value);
+    Ok(())
+}
+
+pub fn push_to_collection(target: DataType, value: DataType) -> Result<(), RuntimeError> {
+    if target.is_string() {
+        let new_chars = to_chars(&value)?;
+        let string = target.get_string()?;
+        string.borrow_mut().extend(new_chars);
+        return Ok(());
+    }
+    target.get_array()?.borrow_mut().push(value);
+    Ok(())
+}
+
+pub fn pop_from_collection(target: DataType) -> Result<DataType, RuntimeError> {
+    if target.is_string() {
+        let popped = target.get_string()?.borrow_mut().pop();
+        return match popped {
+            Some(c) => Ok(DataType::Char(c)),
+            None => Err(RuntimeError::IndexOutOfBound),
+        };
+    }
+    match target.get_array()?.borrow_mut().pop() {
+        Some(item) => Ok(item),
+        None => Err(RuntimeError::IndexOutOfBound),
+    }
+}
+
+// gives back what was removed as a new array or string
+pub fn remove_slice(bounds: SliceBounds, target: DataType) -> Result<DataType, RuntimeError> {
+    if target.is_string() {
+        let removed = edit_chars(&target, |chars| drain_slice(chars, bounds))?;
+        let removed = removed.into_iter().collect();
+        return Ok(DataType::String(Rc::new(RefCell::new(removed))));
+    }
+    if target.is_array() {
+        let removed = drain_slice(&mut target.get_array()?.borrow_mut(), bounds)?;
+        return Ok(DataType::Array(Rc::new(RefCell::new(removed))));
+    }
+    Err(RuntimeError::CannotAccessWithAccessor)
+}
+
+fn drain_slice<T>(items: &mut Vec<T>, bounds: SliceBounds) -> Result<Vec<T>, RuntimeError> {
+    let (start, end, step) = slice_range(bounds, items.len())?;
+    if step == 1 {
+        return Ok(items.drain(start as usize..end.max(start) as usize).collect());
+    }
+    let mut indexes = slice_indexes(bounds, items.len())?;
+    // from the back so the indexes left are not moved
+    indexes.sort_unstable_by(|left, right| right.cmp(left));
+    let mut removed: Vec<T> = indexes.into_iter().map(|index| items.remove(index)).collect();
+    if step > 0 {
+        removed.reverse();
+    }
+    Ok(removed)
+}
+
+// dicts are looped over by their keys in order
+pub fn get_iterable(target: DataType) -> Result<DataType, RuntimeError> {
+    if target.is_dict() {
+        let dict = target.get_dict()?;
+        let keys = dict.borrow().keys().map(|key| key.to_data_type()).collect();
+        return Ok(DataType::Array(Rc::new(RefCell::new(keys))));
+    }
+    Ok(target)
+}
+
+// a rest target allows more items than the targets before it
+pub fn match_length(target: &DataType, count: usize, rest: bool) -> Result<(), RuntimeError> {
+    let length = target.get_array()?.borrow().len();
+    if length == count || (rest && length > count) {
+        return Ok(());
+    }
+    Err(RuntimeError::ShapeMismatch(count, length))
+}
+
+pub fn rest_from(target: &DataType, index: usize) -> Result<DataType, RuntimeError> {
+    let items = target.get_array()?.borrow();
+    let rest = items.iter().skip(index).cloned().collect();
+    Ok(DataType::Array(Rc::new(RefCell::new(rest))))
+}
@@ -0,0 +1,344 @@
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::fmt;
+
+const LIMB_BITS: usize = 32;
+// the largest power of ten in a limb, used to print nine digits at a time
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+
+// sign and magnitude, the magnitude is in base 2^32 with the lowest limb first
+// there are never high zero limbs and zero is never negative so equal numbers are equal structs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> BigInt {
+        trim(&mut limbs);
+        let negative = negative && !limbs.is_empty();
+        BigInt { negative, limbs }
+    }
+
+    pub fn from_i64(int: i64) -> BigInt {
+        let mut magnitude = int.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push(magnitude as u32);
+            magnitude >>= LIMB_BITS;
+        }
+        BigInt::new(int < 0, limbs)
+    }
+
+    // a string of decimal digits
+    pub fn parse(digits: &str) -> Option<BigInt> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut limbs = Vec::new();
+        for c in digits.chars() {
+            let digit = c.to_digit(10)?;
+            mul_small_add(&mut limbs, 10, digit);
+        }
+        Some(BigInt::new(false, limbs))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.limbs.len() > 2 {
+            return None;
+        }
+        let mut magnitude: u64 = 0;
+        for (index, limb) in self.limbs.iter().enumerate() {
+            magnitude |= u64::from(*limb) << (index * LIMB_BITS);
+        }
+        if self.negative {
+            if magnitude > i64::MAX as u64 + 1 {
+                return None;
+            }
+            return Some((magnitude as i64).wrapping_neg());
+        }
+        if magnitude > i64::MAX as u64 {
+            return None;
+        }
+        Some(magnitude as i64)
+    }
+
+    // a whole float exactly, fractions, infinities and NaN have no big integer
+    pub fn from_f64(float: f64) -> Option<BigInt> {
+        if !float.is_finite() || float.fract() != 0.0 {
+            return None;
+        }
+        let bits = float.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i32;
+        if exponent == 0 {
+            // zero, the other floats without an exponent are fractions
+            return Some(BigInt::from_i64(0));
+        }
+        let mantissa = ((bits & ((1 << 52) - 1)) | (1 << 52)) as i64;
+        let shift = exponent - 1075;
+        let magnitude = if shift < 0 {
+            BigInt::from_i64(mantissa >> -shift)
+        } else {
+            &BigInt::from_i64(mantissa) * &BigInt::from_i64(2).pow(shift as u32)
+        };
+        if float < 0.0 {
+            return Some(-&magnitude);
+        }
+        Some(magnitude)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let mut float = 0.0;
+        for limb in self.limbs.iter().rev() {
+            float = float * 4_294_967_296.0 + f64::from(*limb);
+        }
+        if self.negative {
+            -float
+        } else {
+            float
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_odd(&self) -> bool {
+        matches!(self.limbs.first(), Some(limb) if limb & 1 == 1)
+    }
+
+    // the number of bits in the magnitude, 0 for zero
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => (self.limbs.len() * LIMB_BITS) as u64 - u64::from(top.leading_zeros()),
+            None => 0,
+        }
+    }
+
+    // truncated towards zero like i64, the divisor must not be zero
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = div_rem_magnitudes(&self.limbs, &other.limbs);
+        (
+            BigInt::new(self.negative != other.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        )
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+        match cmp_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitudes(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, sub_magnitudes(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_magnitudes(&self.limbs, &other.limbs),
+        )
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut limbs = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while !limbs.is_empty() {
+            chunks.push(div_small(&mut limbs, DECIMAL_CHUNK));
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+fn cmp_magnitudes(left: &[u32], right: &[u32]) -> Ordering {
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let mut carry = 0;
+    for index in 0..left.len().max(right.len()) {
+        let total = u64::from(*left.get(index).unwrap_or(&0))
+            + u64::from(*right.get(index).unwrap_or(&0)) + carry;
+        sum.push(total as u32);
+        carry = total >> LIMB_BITS;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+// the left magnitude must be the larger
+fn sub_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(left.len());
+    let mut borrow = 0;
+    for (index, limb) in left.iter().enumerate() {
+        let mut total = i64::from(*limb) - i64::from(*right.get(index).unwrap_or(&0)) - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << LIMB_BITS;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    trim(&mut difference);
+    difference
+}
+
+fn mul_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut product = vec![0; left.len() + right.len()];
+    for (left_index, left_limb) in left.iter().enumerate() {
+        let mut carry = 0;
+        for (right_index, right_limb) in right.iter().enumerate() {
+            let total = u64::from(product[left_index + right_index])
+                + u64::from(*left_limb) * u64::from(*right_limb) + carry;
+            product[left_index + right_index] = total as u32;
+            carry = total >> LIMB_BITS;
+        }
+        product[left_index + right.len()] = carry as u32;
+    }
+    trim(&mut product);
+    product
+}
+
+fn mul_small_add(limbs: &mut Vec<u32>, mul: u32, add: u32) {
+    let mut carry = u64::from(add);
+    for limb in limbs.iter_mut() {
+        let total = u64::from(*limb) * u64::from(mul) + carry;
+        *limb = total as u32;
+        carry = total >> LIMB_BITS;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+// divide in place giving back the remainder
+fn div_small(limbs: &mut Vec<u32>, div: u32) -> u32 {
+    let mut remainder = 0;
+    for limb in limbs.iter_mut().rev() {
+        let total = (remainder << LIMB_BITS) | u64::from(*limb);
+        *limb = (total / u64::from(div)) as u32;
+        remainder = total % u64::from(div);
+    }
+    trim(limbs);
+    remainder as u32
+}
+
+// long division one bit at a time
+fn div_rem_magnitudes(left: &[u32], right: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if right.len() == 1 {
+        let mut quotient = left.to_vec();
+        let remainder = div_small(&mut quotient, right[0]);
+        return (quotient, vec![remainder]);
+    }
+    let mut quotient = vec![0; left.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..left.len() * LIMB_BITS).rev() {
+        let next = (left[bit / LIMB_BITS] >> (bit % LIMB_BITS)) & 1;
+        shift_left_one(&mut remainder, next);
+        if cmp_magnitudes(&remainder, right) != Ordering::Less {
+            remainder = sub_magnitudes(&remainder, right);
+            quotient[bit / LIMB_BITS] |= 1 << (bit % LIMB_BITS);
+        }
+    }
+    (quotient, remainder)
+}
+
+fn shift_left_one(limbs: &mut Vec<u32>, low_bit: u32) {
+    let mut carry = low_bit;
+    for limb in limbs.iter_mut() {
+        let next = *limb >> 31;
+        *limb = (*limb << 1) | carry;
+        carry = next;
+    }
+    if carry > 0 {
+        limbs.push(carry);
+    }
+}
@@ -1,35 +1,117 @@
+use super::super::super::big_int::BigInt;
+use super::super::super::command::SharedCommands;
+
 pub type AstArgs = Vec<Vec<Vec<Ast>>>;
 
 pub type AstBody = Vec<Vec<Ast>>;
 
+// the start, end and step of a slice, a part left off is empty
+pub type AstSlice = Vec<AstBody>;
+
+// catch vars, catch body
+pub type AstCatch = (AstArgs, AstBody);
+
+// body, catch, finally body
+pub type AstTry = (AstBody, Option<AstCatch>, Option<AstBody>);
+
+// where a destructuring assign saves each item,
+// a nested pattern has a slot to hold the array it takes apart
+#[derive(Debug, Clone)]
+pub enum AstPattern {
+    Var(Ast),
+    // the items left after the ones before it
+    Rest(Ast),
+    Nested(Vec<AstPattern>, Ast),
+}
+
+// an arg written as name: value
+pub fn named_arg(arg: &[Vec<Ast>]) -> Option<(&str, &AstBody)> {
+    if let [ref statement] = *arg {
+        if let [Ast::Named(ref name, ref value)] = statement.as_slice() {
+            return Some((name, value));
+        }
+    }
+    None
+}
+
 #[derive(Debug, Clone)]
 pub enum Ast {
     End,
     Comment(String),
     Var(String),
+    // a name written global
+    GlobalVar(String),
     VarArg(String, usize),
     VarLocal(String, usize),
+    VarUpvalue(String, usize),
+    VarGlobal(String, usize),
     Bool(bool),
     Integer(i64),
+    BigInt(BigInt),
     Float(f64),
     Char(char),
     String(String),
     Array(AstArgs),
+    // a string with {} parts, each part is shown and added in order
+    Interpolate(AstArgs),
+    // key and value of each item
+    Dict(Vec<(AstBody, AstBody)>),
     Access(AstBody),
     AccessAssign(AstBody, AstBody),
     AccessCall(AstBody, AstArgs),
+    Slice(AstSlice),
+    SliceAssign(AstSlice, AstBody),
+    Has(AstBody),
+    // an empty index is the end of the collection
+    Remove(AstBody),
+    RemoveSlice(AstSlice),
+    Insert(AstBody, AstBody),
     Len,
     Group(AstBody),
     // args, body
     Function(AstArgs, AstBody),
+    // marks the arg after it as collecting the extra args
+    Rest,
+    // an arg given by name or an arg with a default value
+    Named(String, AstBody),
+    // separates the targets of a destructuring assign
+    Comma,
+    Destructure(Box<AstPattern>, AstBody),
+    // args, body, captured vars
+    Closure(AstArgs, AstBody, Vec<Ast>),
+    // file name
+    Import(String),
+    // namespace slot, module commands
+    Module(usize, SharedCommands),
     // args
     FunctionCall(AstArgs),
     FunctionSelfCall(AstArgs),
     // body
     If(AstBody),
+    // body, else body
+    IfElse(AstBody, AstBody),
+    // body, stack length slot
+    While(AstBody, Vec<Ast>),
+    // item and index vars, body, collection, index and stack length slots
+    Each(AstArgs, AstBody, Vec<Ast>),
+    // blocks, error slot
+    Try(Box<AstTry>, Vec<Ast>),
+    Throw,
+    Break,
+    Continue,
     Return,
     Assign(AstBody),
     Equals,
+    NotEquals,
+    Identical,
+    LessThan,
+    LessEquals,
+    GreaterThan,
+    GreaterEquals,
+    And,
+    Or,
+    Not,
+    Neg,
     Add,
     Concat,
     Sub,
@@ -44,33 +126,172 @@ pub enum Ast {
 impl Ast {
     pub fn add_end(&self) -> bool {
         match *self {
-            Ast::If(_) => true,
+            Ast::If(_)
+            | Ast::IfElse(_, _)
+            | Ast::While(_, _)
+            | Ast::Each(_, _, _)
+            | Ast::Try(_, _) => true,
             _ => false,
         }
     }
 
     pub fn presedence(&self) -> usize {
         match *self {
-            Ast::End | Ast::Comment(_) => 0,
+            Ast::End | Ast::Comment(_) | Ast::Comma => 0,
+            Ast::Rest
+            | Ast::Named(_, _)
+            | Ast::Var(_)
+            | Ast::GlobalVar(_)
+            | Ast::VarLocal(_, _)
+            | Ast::VarArg(_, _)
+            | Ast::VarUpvalue(_, _)
+            | Ast::VarGlobal(_, _)
+            | Ast::Bool(_)
+            | Ast::Integer(_)
+            | Ast::BigInt(_)
+            | Ast::Float(_)
+            | Ast::Char(_)
+            | Ast::String(_)
+            | Ast::Interpolate(_)
+            | Ast::Array(_)
+            | Ast::Dict(_)
+            | Ast::Function(_, _)
+            | Ast::Closure(_, _, _)
+            | Ast::Import(_)
+            | Ast::Module(_, _) => 1,
+            Ast::If(_)
+            | Ast::IfElse(_, _)
+            | Ast::While(_, _)
+            | Ast::Each(_, _, _)
+            | Ast::Try(_, _)
+            | Ast::Throw
+            | Ast::Break
+            | Ast::Continue
+            | Ast::Assign(_)
+            | Ast::Destructure(_, _)
+            | Ast::Return
+            | Ast::IoWrite
+            | Ast::IoAppend => 2,
+            Ast::Or => 3,
+            Ast::And => 4,
+            Ast::Not => 5,
+            Ast::Equals
+            | Ast::NotEquals
+            | Ast::Identical
+            | Ast::LessThan
+            | Ast::LessEquals
+            | Ast::GreaterThan
+            | Ast::GreaterEquals
+            | Ast::Concat => 6,
+            Ast::Add | Ast::Sub => 7,
+            Ast::Mul | Ast::Div | Ast::Rem => 8,
+            Ast::Neg => 9,
+            Ast::Exp => 10,
+            Ast::Len => 11,
+            Ast::FunctionCall(_) | Ast::FunctionSelfCall(_) => 12,
+            Ast::Access(_)
+            | Ast::AccessAssign(_, _)
+            | Ast::AccessCall(_, _)
+            | Ast::Slice(_)
+            | Ast::SliceAssign(_, _)
+            | Ast::Has(_)
+            | Ast::Remove(_)
+            | Ast::RemoveSlice(_)
+            | Ast::Insert(_, _) => 13,
+            Ast::Group(_) => 14,
+        }
+    }
+
+    // prefix ops have no left side so they never pop the op stack
+    pub fn is_prefix(&self) -> bool {
+        matches!(*self, Ast::Not | Ast::Neg | Ast::Len)
+    }
+
+    // a value is complete after this so a - that follows is a subtraction
+    pub fn ends_operand(&self) -> bool {
+        match *self {
+            Ast::Group(_)
+            | Ast::FunctionCall(_)
+            | Ast::FunctionSelfCall(_)
+            | Ast::Access(_)
+            | Ast::AccessCall(_, _)
+            | Ast::Slice(_)
+            | Ast::Has(_)
+            | Ast::Remove(_)
+            | Ast::RemoveSlice(_) => true,
+            _ => self.presedence() == 1,
+        }
+    }
+
+    // (values popped, values pushed) once shunted
+    pub fn stack_effect(&self) -> (usize, usize) {
+        match *self {
+            Ast::End
+            | Ast::Comment(_)
+            | Ast::Return
+            | Ast::Break
+            | Ast::Continue
+            | Ast::Rest
+            | Ast::Comma
+            | Ast::Destructure(_, _)
+            | Ast::Try(_, _) => (0, 0),
             Ast::Var(_)
+            | Ast::GlobalVar(_)
             | Ast::VarLocal(_, _)
             | Ast::VarArg(_, _)
+            | Ast::VarUpvalue(_, _)
+            | Ast::VarGlobal(_, _)
             | Ast::Bool(_)
             | Ast::Integer(_)
+            | Ast::BigInt(_)
             | Ast::Float(_)
             | Ast::Char(_)
             | Ast::String(_)
+            | Ast::Interpolate(_)
             | Ast::Array(_)
-            | Ast::Function(_, _) => 1,
-            Ast::If(_) | Ast::Assign(_) | Ast::Return | Ast::IoWrite | Ast::IoAppend => 2,
-            Ast::Equals | Ast::Concat => 3,
-            Ast::Add | Ast::Sub => 4,
-            Ast::Mul | Ast::Div | Ast::Rem => 5,
-            Ast::Exp => 6,
-            Ast::Len => 7,
-            Ast::FunctionCall(_) | Ast::FunctionSelfCall(_) => 8,
-            Ast::Access(_) | Ast::AccessAssign(_, _) | Ast::AccessCall(_, _) => 9,
-            Ast::Group(_) => 10,
+            | Ast::Dict(_)
+            | Ast::Function(_, _)
+            | Ast::Closure(_, _, _)
+            | Ast::Import(_)
+            | Ast::Module(_, _)
+            | Ast::Group(_)
+            | Ast::Named(_, _)
+            | Ast::FunctionSelfCall(_) => (0, 1),
+            Ast::Len
+            | Ast::Not
+            | Ast::Neg
+            | Ast::Access(_)
+            | Ast::AccessCall(_, _)
+            | Ast::Slice(_)
+            | Ast::Has(_)
+            | Ast::Remove(_)
+            | Ast::RemoveSlice(_)
+            | Ast::FunctionCall(_)
+            | Ast::IfElse(_, _) => (1, 1),
+            Ast::If(_)
+            | Ast::While(_, _)
+            | Ast::Each(_, _, _)
+            | Ast::Throw
+            | Ast::Assign(_)
+            | Ast::AccessAssign(_, _)
+            | Ast::SliceAssign(_, _)
+            | Ast::Insert(_, _) => (1, 0),
+            Ast::Concat | Ast::IoWrite | Ast::IoAppend => (2, 0),
+            Ast::Equals
+            | Ast::NotEquals
+            | Ast::Identical
+            | Ast::LessThan
+            | Ast::LessEquals
+            | Ast::GreaterThan
+            | Ast::GreaterEquals
+            | Ast::And
+            | Ast::Or
+            | Ast::Add
+            | Ast::Sub
+            | Ast::Mul
+            | Ast::Div
+            | Ast::Rem
+            | Ast::Exp => (2, 1),
         }
     }
 
@@ -83,11 +304,23 @@ impl Ast {
 
     pub fn is_var(&self) -> bool {
         match *self {
-            Ast::Var(_) | Ast::VarArg(_, _) | Ast::VarLocal(_, _) => true,
+            Ast::Var(_)
+            | Ast::GlobalVar(_)
+            | Ast::VarArg(_, _)
+            | Ast::VarLocal(_, _)
+            | Ast::VarUpvalue(_, _)
+            | Ast::VarGlobal(_, _) => true,
             _ => false,
         }
     }
 
+    pub fn is_interpolate(&self) -> Option<&AstArgs> {
+        if let Ast::Interpolate(ref parts) = *self {
+            return Some(parts);
+        }
+        None
+    }
+
     pub fn is_array(&self) -> Option<&AstArgs> {
         if let Ast::Array(ref items) = *self {
             return Some(items);
@@ -102,6 +335,55 @@ impl Ast {
         None
     }
 
+    pub fn is_dict(&self) -> Option<&Vec<(AstBody, AstBody)>> {
+        if let Ast::Dict(ref items) = *self {
+            return Some(items);
+        }
+        None
+    }
+
+    pub fn is_has(&self) -> Option<&AstBody> {
+        if let Ast::Has(ref body) = *self {
+            return Some(body);
+        }
+        None
+    }
+
+    pub fn is_remove(&self) -> Option<&AstBody> {
+        if let Ast::Remove(ref body) = *self {
+            return Some(body);
+        }
+        None
+    }
+
+    pub fn is_remove_slice(&self) -> Option<&AstSlice> {
+        if let Ast::RemoveSlice(ref parts) = *self {
+            return Some(parts);
+        }
+        None
+    }
+
+    pub fn is_insert(&self) -> Option<(&AstBody, &AstBody)> {
+        if let Ast::Insert(ref index_body, ref value_body) = *self {
+            return Some((index_body, value_body));
+        }
+        None
+    }
+
+    pub fn is_slice(&self) -> Option<&AstSlice> {
+        if let Ast::Slice(ref parts) = *self {
+            return Some(parts);
+        }
+        None
+    }
+
+    pub fn is_slice_assign(&self) -> Option<(&AstSlice, &AstBody)> {
+        if let Ast::SliceAssign(ref parts, ref assign_body) = *self {
+            return Some((parts, assign_body));
+        }
+        None
+    }
+
     pub fn is_access_assign(&self) -> Option<(&AstBody, &AstBody)> {
         if let Ast::AccessAssign(ref access_body, ref assign_body) = *self {
             return Some((access_body, assign_body));
@@ -123,6 +405,17 @@ impl Ast {
         }
     }
 
+    pub fn is_logic(&self) -> bool {
+        matches!(*self, Ast::And | Ast::Or)
+    }
+
+    pub fn is_closure(&self) -> Option<(&AstArgs, &AstBody, &Vec<Ast>)> {
+        if let Ast::Closure(ref args, ref body, ref upvalues) = *self {
+            return Some((args, body, upvalues));
+        }
+        None
+    }
+
     pub fn is_group(&self) -> Option<&AstBody> {
         if let Ast::Group(ref body) = *self {
             return Some(body);
@@ -151,6 +444,48 @@ impl Ast {
         None
     }
 
+    pub fn is_if_else(&self) -> Option<(&AstBody, &AstBody)> {
+        if let Ast::IfElse(ref body, ref else_body) = *self {
+            return Some((body, else_body));
+        }
+        None
+    }
+
+    pub fn is_while(&self) -> Option<(&AstBody, &[Ast])> {
+        if let Ast::While(ref body, ref slots) = *self {
+            return Some((body, slots));
+        }
+        None
+    }
+
+    pub fn is_try(&self) -> Option<(&AstTry, &Vec<Ast>)> {
+        if let Ast::Try(ref blocks, ref slots) = *self {
+            return Some((blocks, slots));
+        }
+        None
+    }
+
+    pub fn is_each(&self) -> Option<(&AstArgs, &AstBody, &Vec<Ast>)> {
+        if let Ast::Each(ref vars, ref body, ref slots) = *self {
+            return Some((vars, body, slots));
+        }
+        None
+    }
+
+    pub fn is_comma(&self) -> bool {
+        if let Ast::Comma = *self {
+            return true;
+        }
+        false
+    }
+
+    pub fn is_destructure(&self) -> Option<(&AstPattern, &AstBody)> {
+        if let Ast::Destructure(ref pattern, ref body) = *self {
+            return Some((pattern, body));
+        }
+        None
+    }
+
     pub fn is_assign(&self) -> Option<&AstBody> {
         if let Ast::Assign(ref body) = *self {
             return Some(body);
@@ -162,6 +497,7 @@ impl Ast {
         match *self {
             Ast::Bool(_)
             | Ast::Integer(_)
+            | Ast::BigInt(_)
             | Ast::Float(_)
             | Ast::Char(_)
             | Ast::String(_)
@@ -173,7 +509,11 @@ impl Ast {
 
     pub fn can_call(&self) -> bool {
         match *self {
-            Ast::VarArg(_, _) | Ast::VarLocal(_, _) | Ast::Function(_, _) => true,
+            Ast::VarArg(_, _)
+            | Ast::VarLocal(_, _)
+            | Ast::VarUpvalue(_, _)
+            | Ast::VarGlobal(_, _)
+            | Ast::Function(_, _) => true,
             _ => false,
         }
     }
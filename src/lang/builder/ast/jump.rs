@@ -1,11 +1,17 @@
-use super::ast::{Ast, AstArgs, AstBody};
+use super::ast::{named_arg, Ast, AstArgs, AstBody, AstPattern};
 use super::super::super::error::ParserError;
 use super::super::symbol::{SymbolTable, SymbolType};
+use super::super::module::load_module;
 
 pub fn shunt_yard(ast: &mut Vec<Ast>, symbols: &mut SymbolTable) -> Result<Vec<Ast>, ParserError> {
+    if let Some(destructure) = shunt_destructure(ast, symbols)? {
+        return Ok(vec![destructure]);
+    }
     ast.reverse();
     let mut op_stack: Vec<Ast> = Vec::new();
     let mut result_stack = Vec::new();
+    // a - with no value to its left is a negation
+    let mut expect_operand = true;
     while let Some(mut op) = ast.pop() {
         let presedence = op.presedence();
         if presedence == 0 {
@@ -14,41 +20,117 @@ pub fn shunt_yard(ast: &mut Vec<Ast>, symbols: &mut SymbolTable) -> Result<Vec<A
         // shunt body
         op = match op {
             Ast::Function(ref mut args, ref mut body) => {
-                let mut new_symbols = SymbolTable::new();
-                new_symbols.set_arg_mode();
-                let new_args = shunt_args(args, &mut new_symbols)?;
-                new_symbols.set_local_mode();
-                let new_body = shunt_body(body, &mut new_symbols)?;
-                Ast::Function(new_args, new_body)
+                symbols.push_scope();
+                let shunted = shunt_function(args, body, symbols);
+                // always leave the function scope so the table can be reused after an error
+                let upvalues = symbols.pop_scope();
+                let (new_args, new_body) = shunted?;
+                if upvalues.is_empty() {
+                    Ast::Function(new_args, new_body)
+                } else {
+                    let upvalues = upvalues
+                        .into_iter()
+                        .map(|(name, symbol_type)| symbol_to_ast(&name, symbol_type))
+                        .collect();
+                    Ast::Closure(new_args, new_body, upvalues)
+                }
+            }
+            Ast::Import(ref name) => {
+                let (slot, commands) = load_module(name, symbols)?;
+                Ast::Module(slot, commands)
             }
             Ast::Group(ref mut body) => Ast::Group(shunt_body(body, symbols)?),
             Ast::Array(ref mut items) => Ast::Array(shunt_args(items, symbols)?),
+            Ast::Interpolate(ref mut parts) => Ast::Interpolate(shunt_args(parts, symbols)?),
+            Ast::Dict(ref mut items) => {
+                let mut new_items = Vec::new();
+                for (key, value) in items.iter_mut() {
+                    new_items.push((shunt_body(key, symbols)?, shunt_body(value, symbols)?));
+                }
+                Ast::Dict(new_items)
+            }
             Ast::Access(ref mut body) => Ast::Access(shunt_body(body, symbols)?),
+            Ast::Has(ref mut body) => Ast::Has(shunt_body(body, symbols)?),
+            Ast::Remove(ref mut body) => Ast::Remove(shunt_body(body, symbols)?),
+            Ast::RemoveSlice(ref mut parts) => Ast::RemoveSlice(shunt_args(parts, symbols)?),
+            Ast::Insert(ref mut index_body, ref mut value_body) => Ast::Insert(
+                shunt_body(index_body, symbols)?,
+                shunt_body(value_body, symbols)?,
+            ),
             Ast::AccessAssign(ref mut access_body, ref mut assign_body) => Ast::AccessAssign(
                 shunt_body(access_body, symbols)?,
                 shunt_body(assign_body, symbols)?,
             ),
+            Ast::Slice(ref mut parts) => Ast::Slice(shunt_args(parts, symbols)?),
+            Ast::SliceAssign(ref mut parts, ref mut assign_body) => Ast::SliceAssign(
+                shunt_args(parts, symbols)?,
+                shunt_body(assign_body, symbols)?,
+            ),
             Ast::AccessCall(ref mut access_body, ref mut items) => Ast::AccessCall(
                 shunt_body(access_body, symbols)?,
                 shunt_args(items, symbols)?,
             ),
             Ast::FunctionCall(ref mut args) => Ast::FunctionCall(shunt_args(args, symbols)?),
             Ast::FunctionSelfCall(ref mut args) => {
-                Ast::FunctionSelfCall(shunt_args(args, symbols)?)
+                let new_args = shunt_args(args, symbols)?;
+                Ast::FunctionSelfCall(resolve_named_args(new_args, symbols.args())?)
+            }
+            Ast::Named(ref name, ref mut value) => {
+                Ast::Named(name.clone(), shunt_body(value, symbols)?)
             }
             Ast::If(ref mut body) => Ast::If(shunt_body(body, symbols)?),
+            Ast::IfElse(ref mut body, ref mut else_body) => Ast::IfElse(
+                shunt_body(body, symbols)?,
+                shunt_body(else_body, symbols)?,
+            ),
+            Ast::While(ref mut body, _) => {
+                let slots = vec![symbol_to_ast("@stack", symbols.reserve())];
+                Ast::While(shunt_body(body, symbols)?, slots)
+            }
+            Ast::Each(ref mut vars, ref mut body, _) => {
+                let slots = vec![
+                    symbol_to_ast("@collection", symbols.reserve()),
+                    symbol_to_ast("@index", symbols.reserve()),
+                    symbol_to_ast("@stack", symbols.reserve()),
+                ];
+                let new_vars = shunt_targets(vars, symbols);
+                Ast::Each(new_vars, shunt_body(body, symbols)?, slots)
+            }
+            Ast::Try(ref mut blocks, _) => {
+                let slots = vec![
+                    symbol_to_ast("@error", symbols.reserve()),
+                    symbol_to_ast("@stack", symbols.reserve()),
+                ];
+                let (ref mut body, ref mut catch, ref mut finally) = **blocks;
+                let new_body = shunt_body(body, symbols)?;
+                let new_catch = match *catch {
+                    Some((ref mut vars, ref mut catch_body)) => Some((
+                        shunt_targets(vars, symbols),
+                        shunt_body(catch_body, symbols)?,
+                    )),
+                    None => None,
+                };
+                let new_finally = match *finally {
+                    Some(ref mut finally_body) => Some(shunt_body(finally_body, symbols)?),
+                    None => None,
+                };
+                Ast::Try(Box::new((new_body, new_catch, new_finally)), slots)
+            }
             Ast::Assign(ref mut body) => Ast::Assign(shunt_body(body, symbols)?),
+            Ast::GlobalVar(ref name) => symbol_to_ast(name, symbols.get_global(name)),
+            Ast::Sub if expect_operand => negate(ast),
             _ => op,
         };
+        let presedence = op.presedence();
+        expect_operand = !op.ends_operand();
         if presedence == 1 {
             let do_push = match op.has_var_name() {
                 Some(name) => {
-                    match symbols.get(name) {
-                        SymbolType::Arg(id) => result_stack.push(Ast::VarArg(name.to_string(), id)),
-                        SymbolType::Local(id) => {
-                            result_stack.push(Ast::VarLocal(name.to_string(), id))
-                        }
+                    let symbol_type = match ast.last().and_then(|next| next.is_assign()) {
+                        Some(_) => symbols.get_target(name),
+                        None => symbols.get(name),
                     };
+                    result_stack.push(symbol_to_ast(name, symbol_type));
                     false
                 }
                 None => true,
@@ -57,21 +139,12 @@ pub fn shunt_yard(ast: &mut Vec<Ast>, symbols: &mut SymbolTable) -> Result<Vec<A
                 result_stack.push(op);
             }
         } else {
-            let mut clear_op_stack = false;
-            let mut add_last = false;
-            if let Some(last) = op_stack.last() {
-                // compare presedence
-                let last_presedence = last.presedence();
-                if presedence < last_presedence {
-                    clear_op_stack = true;
-                } else if presedence == last_presedence {
-                    add_last = true;
+            // move every op that binds at least as tight to the result
+            // prefix ops have nothing to their left to bind to
+            while let Some(last_presedence) = op_stack.last().map(|last| last.presedence()) {
+                if op.is_prefix() || presedence > last_presedence {
+                    break;
                 }
-            }
-            if clear_op_stack {
-                op_stack.reverse();
-                result_stack.append(&mut op_stack);
-            } else if add_last {
                 result_stack.push(op_stack.pop().unwrap());
             }
             op_stack.push(op);
@@ -84,6 +157,139 @@ pub fn shunt_yard(ast: &mut Vec<Ast>, symbols: &mut SymbolTable) -> Result<Vec<A
     Ok(result_stack)
 }
 
+// a, b = value
+fn shunt_destructure(ast: &[Ast], symbols: &mut SymbolTable) -> Result<Option<Ast>, ParserError> {
+    if !ast.iter().any(|op| op.is_comma()) {
+        return Ok(None);
+    }
+    let mut body = match ast.last().and_then(|last| last.is_assign()) {
+        Some(body) => body.clone(),
+        None => return Ok(None),
+    };
+    let body = shunt_body(&mut body, symbols)?;
+    let pattern = shunt_pattern(&ast[..ast.len() - 1], symbols)?;
+    Ok(Some(Ast::Destructure(Box::new(pattern), body)))
+}
+
+// only the last target can be a rest target
+fn shunt_pattern(targets: &[Ast], symbols: &mut SymbolTable) -> Result<AstPattern, ParserError> {
+    let parts: Vec<&[Ast]> = targets.split(|op| op.is_comma()).collect();
+    let mut items = Vec::new();
+    for (index, part) in parts.iter().enumerate() {
+        let item = match *part {
+            [Ast::Var(ref name)] => AstPattern::Var(symbol_to_ast(name, symbols.get_target(name))),
+            [Ast::Rest, Ast::Var(ref name)] if index + 1 == parts.len() => {
+                AstPattern::Rest(symbol_to_ast(name, symbols.get_target(name)))
+            }
+            [Ast::Group(ref body)] if body.len() == 1 => shunt_pattern(&body[0], symbols)?,
+            _ => return Err(ParserError::InvalidPattern),
+        };
+        items.push(item);
+    }
+    let slot = symbol_to_ast("@destructure", symbols.reserve());
+    Ok(AstPattern::Nested(items, slot))
+}
+
+// fold the sign into a number unless a power binds tighter to it
+fn negate(ast: &mut Vec<Ast>) -> Ast {
+    if ast.len() > 1 {
+        if let Ast::Exp = ast[ast.len() - 2] {
+            return Ast::Neg;
+        }
+    }
+    match ast.last().cloned() {
+        Some(Ast::Integer(int)) => {
+            ast.pop();
+            Ast::Integer(-int)
+        }
+        Some(Ast::BigInt(big)) => {
+            ast.pop();
+            let big = -&big;
+            match big.to_i64() {
+                Some(int) => Ast::Integer(int),
+                None => Ast::BigInt(big),
+            }
+        }
+        Some(Ast::Float(float)) => {
+            ast.pop();
+            Ast::Float(-float)
+        }
+        _ => Ast::Neg,
+    }
+}
+
+fn symbol_to_ast(name: &str, symbol_type: SymbolType) -> Ast {
+    match symbol_type {
+        SymbolType::Arg(id) => Ast::VarArg(name.to_string(), id),
+        SymbolType::Local(id) => Ast::VarLocal(name.to_string(), id),
+        SymbolType::Upvalue(id) => Ast::VarUpvalue(name.to_string(), id),
+        SymbolType::Global(id) => Ast::VarGlobal(name.to_string(), id),
+    }
+}
+
+fn shunt_function(
+    args: &mut AstArgs,
+    body: &mut AstBody,
+    symbols: &mut SymbolTable,
+) -> Result<(AstArgs, AstBody), ParserError> {
+    symbols.set_arg_mode();
+    let mut new_args = Vec::new();
+    let mut arg_names = Vec::new();
+    for arg in args.iter_mut() {
+        // the default is shunted with the locals once every arg is known
+        if let Some((name, _)) = named_arg(arg) {
+            symbols.get(name);
+            arg_names.push(name.to_string());
+            new_args.push(arg.clone());
+            continue;
+        }
+        let mut new_statements = Vec::new();
+        for statements in arg.iter_mut() {
+            new_statements.push(shunt_yard(statements, symbols)?);
+        }
+        if let [ref statement] = *new_statements {
+            if let [Ast::VarArg(ref name, _)] = statement.as_slice() {
+                arg_names.push(name.clone());
+            }
+        }
+        new_args.push(new_statements);
+    }
+    symbols.set_local_mode();
+    symbols.set_args(arg_names);
+    for arg in new_args.iter_mut() {
+        for statement in arg.iter_mut() {
+            for ast in statement.iter_mut() {
+                if let Ast::Named(_, ref mut default) = *ast {
+                    *default = shunt_body(default, symbols)?;
+                }
+            }
+        }
+    }
+    Ok((new_args, shunt_body(body, symbols)?))
+}
+
+// the args of the function are known in a self call so the names can be checked,
+// args given by name in the order they are taken are passed by position
+fn resolve_named_args(args: AstArgs, arg_names: &[String]) -> Result<AstArgs, ParserError> {
+    let mut new_args = Vec::new();
+    let mut by_position = true;
+    for arg in args {
+        let named = named_arg(&arg).map(|(name, value)| (name.to_string(), value.clone()));
+        match named {
+            Some((name, value)) => match arg_names.iter().position(|arg_name| *arg_name == name) {
+                Some(index) if by_position && index == new_args.len() => new_args.push(value),
+                Some(_) => {
+                    by_position = false;
+                    new_args.push(arg);
+                }
+                None => return Err(ParserError::UnknownArgument(name)),
+            },
+            None => new_args.push(arg),
+        }
+    }
+    Ok(new_args)
+}
+
 fn shunt_args(args: &mut AstArgs, symbols: &mut SymbolTable) -> Result<AstArgs, ParserError> {
     let mut new_args = Vec::new();
     for arg in args.iter_mut() {
@@ -96,6 +302,26 @@ fn shunt_args(args: &mut AstArgs, symbols: &mut SymbolTable) -> Result<AstArgs,
     Ok(new_args)
 }
 
+// vars that are bound by a statement rather than read
+fn shunt_targets(args: &mut AstArgs, symbols: &mut SymbolTable) -> AstArgs {
+    let mut new_args = Vec::new();
+    for arg in args.iter_mut() {
+        let mut new_statements = Vec::new();
+        for statement in arg.drain(..) {
+            let new_statement = statement
+                .into_iter()
+                .map(|op| match op.has_var_name() {
+                    Some(name) => symbol_to_ast(name, symbols.get_target(name)),
+                    None => op,
+                })
+                .collect();
+            new_statements.push(new_statement);
+        }
+        new_args.push(new_statements);
+    }
+    new_args
+}
+
 fn shunt_body(body: &mut AstBody, symbols: &mut SymbolTable) -> Result<AstBody, ParserError> {
     let mut new_body = Vec::new();
     for statement in body.iter_mut() {
@@ -3,7 +3,8 @@ mod jump;
 
 use std::str::Chars;
 use std::iter::Peekable;
-pub use self::ast::{Ast, AstArgs, AstBody};
+pub use self::ast::{named_arg, Ast, AstArgs, AstBody, AstPattern, AstSlice, AstTry};
+use super::super::big_int::BigInt;
 use super::super::error::ParserError;
 pub use self::jump::shunt_yard;
 
@@ -27,6 +28,11 @@ pub fn load_ast_til_end(iter: &mut Peekable<Chars>) -> Result<Vec<Ast>, ParserEr
     Ok(ast)
 }
 
+// looks at the chars coming next without taking them
+fn starts_with(iter: &Peekable<Chars>, text: &str) -> bool {
+    iter.clone().take(text.len()).eq(text.chars())
+}
+
 fn match_ast(iter: &mut Peekable<Chars>) -> Result<Option<Ast>, ParserError> {
     let c = match iter.peek() {
         Some(c) => *c,
@@ -59,9 +65,14 @@ fn match_ast(iter: &mut Peekable<Chars>) -> Result<Option<Ast>, ParserError> {
                 let access_body = load_block(iter, '[', ']')?;
                 let (args, _) = load_items(iter, "\n;{")?;
                 return Ok(Some(Ast::AccessCall(access_body, args)));
-            } else if c == '.' {
+            } else if c == '.' && !starts_with(iter, "...") {
+                // not ....rest, a function taking only a rest arg
                 self_call = true;
                 iter.next();
+                if let Some(&'.') = iter.peek() {
+                    iter.next();
+                    return Ok(Some(Ast::Rest));
+                }
             }
             let (args, end_c) = load_items(iter, "\n;{")?;
             if end_c == '{' {
@@ -87,25 +98,65 @@ fn match_ast(iter: &mut Peekable<Chars>) -> Result<Option<Ast>, ParserError> {
                     iter.next();
                     return Ok(Some(Ast::Array(items)));
                 }
+                '{' => Ok(Some(load_dict(iter)?)),
                 '?' => {
                     iter.next();
                     return Ok(Some(Ast::Len));
                 }
+                '@' => {
+                    iter.next();
+                    let (vars, _) = load_items(iter, "{")?;
+                    Ok(Some(Ast::Each(vars, load_block(iter, '{', '}')?, Vec::new())))
+                }
                 _ => return Err(ParserError::InvalidArrayItem),
             }
         }
-        '[' => return Ok(Some(Ast::Access(load_block(iter, '[', ']')?))),
-        '?' => return Ok(Some(Ast::If(load_block(iter, '{', '}')?))),
+        '[' => {
+            let mut parts = load_access(iter)?;
+            if parts.len() == 1 {
+                return Ok(Some(Ast::Access(parts.pop().unwrap())));
+            }
+            Ok(Some(Ast::Slice(parts)))
+        }
+        '?' => {
+            iter.next();
+            match iter.peek() {
+                Some(&'?') => {
+                    iter.next();
+                    return Ok(Some(Ast::While(load_block(iter, '{', '}')?, Vec::new())));
+                }
+                Some(&'[') => return Ok(Some(Ast::Has(load_block(iter, '[', ']')?))),
+                _ => {}
+            }
+            Ok(Some(load_if(iter)?))
+        }
         '=' => return Ok(Some(load_equals(iter)?)),
         '+' => {
+            if let Some('[') = iter.clone().nth(1) {
+                iter.next();
+                return Ok(Some(Ast::Insert(
+                    load_block(iter, '[', ']')?,
+                    load_til_end(iter, "\n;}")?,
+                )));
+            }
             return Ok(Some(double_char(
                 iter,
                 '+',
                 (ParserError::InvalidAdd, ParserError::InvalidConcat),
                 (Ast::Add, Ast::Concat),
-            )?))
+            )?));
+        }
+        '-' => {
+            iter.next();
+            if let Some(&'[') = iter.peek() {
+                let mut parts = load_access(iter)?;
+                if parts.len() == 1 {
+                    return Ok(Some(Ast::Remove(parts.pop().unwrap())));
+                }
+                return Ok(Some(Ast::RemoveSlice(parts)));
+            }
+            Ok(Some(Ast::Sub))
         }
-        '-' => return Ok(Some(next_and_return(iter, Ast::Sub))),
         '*' => {
             return Ok(Some(double_char(
                 iter,
@@ -122,21 +173,20 @@ fn match_ast(iter: &mut Peekable<Chars>) -> Result<Option<Ast>, ParserError> {
                 (Ast::Div, Ast::Rem),
             )?))
         }
-        '>' => {
-            return Ok(Some(double_char(
-                iter,
-                '>',
-                (ParserError::InvalidIoWrite, ParserError::InvalidIoAppend),
-                (Ast::IoWrite, Ast::IoAppend),
-            )?))
+        '>' => Ok(Some(load_greater(iter)?)),
+        '<' => Ok(Some(char_or_equals(
+            iter,
+            '<',
+            ParserError::InvalidLessThan,
+            (Ast::LessThan, Ast::LessEquals),
+        )?)),
+        '!' => Ok(Some(load_not_equals(iter)?)),
+        ',' => {
+            iter.next();
+            Ok(Some(Ast::Comma))
         }
         _ => return Ok(None),
-    };
-}
-
-fn next_and_return(iter: &mut Peekable<Chars>, ast: Ast) -> Ast {
-    iter.next();
-    ast
+    }
 }
 
 fn peek_next_char(iter: &mut Peekable<Chars>, error: &ParserError) -> Result<char, ParserError> {
@@ -188,6 +238,86 @@ fn load_block(iter: &mut Peekable<Chars>, start: char, end: char) -> Result<AstB
     Ok(ast)
 }
 
+fn load_if(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
+    let body = load_block(iter, '{', '}')?;
+    // only move past the whitespace if there is an else
+    let mut lookahead = iter.clone();
+    skip_whitespace(&mut lookahead);
+    if lookahead.peek() != Some(&':') {
+        return Ok(Ast::If(body));
+    }
+    *iter = lookahead;
+    iter.next();
+    skip_whitespace(iter);
+    if iter.peek() == Some(&'{') {
+        return Ok(Ast::IfElse(body, load_block(iter, '{', '}')?));
+    }
+    // else if, load the condition through the next ?
+    let mut else_statement = Vec::new();
+    let error = ParserError::InvalidElse;
+    loop {
+        peek_next_char(iter, &error)?;
+        if let Some(statement) = match_ast(iter)? {
+            if let Ast::End = statement {
+                return Err(error);
+            }
+            let add_end = statement.add_end();
+            else_statement.push(statement);
+            if add_end {
+                break;
+            }
+        } else {
+            iter.next();
+        }
+    }
+    Ok(Ast::IfElse(body, vec![else_statement]))
+}
+
+// try { } catch e { } finally { }, either the catch or the finally can be left off
+fn load_try(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
+    let body = load_block(iter, '{', '}')?;
+    let mut catch = None;
+    if load_keyword(iter, "catch") {
+        let (vars, _) = load_items(iter, "{")?;
+        catch = Some((vars, load_block(iter, '{', '}')?));
+    }
+    let mut finally = None;
+    if load_keyword(iter, "finally") {
+        finally = Some(load_block(iter, '{', '}')?);
+    }
+    if catch.is_none() && finally.is_none() {
+        return Err(ParserError::InvalidTry);
+    }
+    Ok(Ast::Try(Box::new((body, catch, finally)), Vec::new()))
+}
+
+// only move past the whitespace if the word is next
+fn load_keyword(iter: &mut Peekable<Chars>, word: &str) -> bool {
+    let mut lookahead = iter.clone();
+    skip_whitespace(&mut lookahead);
+    for c in word.chars() {
+        if lookahead.next() != Some(c) {
+            return false;
+        }
+    }
+    match lookahead.peek() {
+        Some(&c) if c == '_' || c.is_alphanumeric() => false,
+        _ => {
+            *iter = lookahead;
+            true
+        }
+    }
+}
+
+fn skip_whitespace(iter: &mut Peekable<Chars>) {
+    while let Some(c) = iter.peek().cloned() {
+        if !c.is_whitespace() {
+            break;
+        }
+        iter.next();
+    }
+}
+
 fn load_items(
     iter: &mut Peekable<Chars>,
     stop_chars: &str,
@@ -218,6 +348,15 @@ fn load_items(
             }
             args.push(current_arg);
             current_arg = Vec::new();
+        } else if c == ':' {
+            // name: value
+            let name = match (current_arg.is_empty(), current_statements.as_slice()) {
+                (true, [Ast::Var(ref name)]) => name.clone(),
+                _ => return Err(ParserError::InvalidNamedArg),
+            };
+            iter.next();
+            let value_stop_chars = format!(",{}", stop_chars);
+            current_statements = vec![Ast::Named(name, load_til_end(iter, &value_stop_chars)?)];
         } else {
             if let Some(statement) = match_ast(iter)? {
                 if let Ast::End = statement {
@@ -271,6 +410,49 @@ fn load_til_end(iter: &mut Peekable<Chars>, stop_chars: &str) -> Result<AstBody,
     Ok(ast)
 }
 
+// [index] or a slice [start:end:step], any part of a slice can be left off
+fn load_access(iter: &mut Peekable<Chars>) -> Result<AstSlice, ParserError> {
+    iter.next();
+    let mut parts = Vec::new();
+    let error = ParserError::InvalidSlice;
+    loop {
+        parts.push(load_til_end(iter, ":]")?);
+        let c = peek_next_char(iter, &error)?;
+        iter.next();
+        if c == ']' {
+            break;
+        }
+    }
+    if parts.len() > 3 {
+        return Err(error);
+    }
+    Ok(parts)
+}
+
+// @{key: value, ...}
+fn load_dict(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
+    let error = ParserError::InvalidDict;
+    let mut items = Vec::new();
+    iter.next();
+    loop {
+        let key = load_til_end(iter, ":}")?;
+        if peek_next_char(iter, &error)? == '}' {
+            if key.iter().any(|statement| !statement.is_empty()) {
+                return Err(error);
+            }
+            iter.next();
+            break;
+        }
+        iter.next();
+        let value = load_til_end(iter, ",}")?;
+        items.push((key, value));
+        if peek_next_char(iter, &error)? == ',' {
+            iter.next();
+        }
+    }
+    Ok(Ast::Dict(items))
+}
+
 fn load_comment(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
     let mut comment = String::new();
     let error = ParserError::InvalidComment;
@@ -300,12 +482,41 @@ fn load_var(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
             _ => break,
         }
     }
-    if var == "t" {
-        return Ok(Ast::Bool(true));
-    } else if var == "f" {
-        return Ok(Ast::Bool(false));
+    let ast = match var.as_str() {
+        "t" => Ast::Bool(true),
+        "f" => Ast::Bool(false),
+        "and" => Ast::And,
+        "or" => Ast::Or,
+        "not" => Ast::Not,
+        "break" => Ast::Break,
+        "continue" => Ast::Continue,
+        "import" => load_import(iter)?,
+        "try" => load_try(iter)?,
+        "global" => load_global(iter)?,
+        "throw" => Ast::Throw,
+        _ => Ast::Var(var),
+    };
+    Ok(ast)
+}
+
+fn load_global(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
+    skip_whitespace(iter);
+    if !iter.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+        return Err(ParserError::InvalidGlobal);
+    }
+    match load_var(iter)? {
+        Ast::Var(name) => Ok(Ast::GlobalVar(name)),
+        _ => Err(ParserError::InvalidGlobal),
+    }
+}
+
+fn load_import(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
+    skip_whitespace(iter);
+    match load_string(iter) {
+        Ok(Ast::String(name)) => Ok(Ast::Import(name)),
+        Ok(Ast::Char(c)) => Ok(Ast::Import(c.to_string())),
+        _ => Err(ParserError::InvalidImport),
     }
-    Ok(Ast::Var(var))
 }
 
 fn load_number(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
@@ -330,9 +541,18 @@ fn load_number(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
             _ => break,
         }
     }
-    match is_float {
-        true => Ok(Ast::Float(number.parse().unwrap())),
-        false => Ok(Ast::Integer(number.parse().unwrap())),
+    if is_float {
+        return match number.parse() {
+            Ok(float) => Ok(Ast::Float(float)),
+            Err(_) => Err(ParserError::InvalidFloat),
+        };
+    }
+    match number.parse() {
+        Ok(int) => Ok(Ast::Integer(int)),
+        Err(_) => match BigInt::parse(&number) {
+            Some(big) => Ok(Ast::BigInt(big)),
+            None => Err(ParserError::InvalidNumber),
+        },
     }
 }
 
@@ -345,12 +565,20 @@ fn load_string(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
         return Err(error);
     }
     let mut string = String::new();
+    // the text and {} parts of an interpolated string
+    let mut parts = Vec::new();
     let error = ParserError::InvalidString;
     loop {
         let c = peek_next_char(iter, &error)?;
         match c {
             '"' => {
                 iter.next();
+                if !parts.is_empty() {
+                    if !string.is_empty() {
+                        parts.push(vec![vec![Ast::String(string)]]);
+                    }
+                    return Ok(Ast::Interpolate(parts));
+                }
                 if string.len() == 1 {
                     return Ok(Ast::Char(string.pop().unwrap()));
                 }
@@ -364,10 +592,19 @@ fn load_string(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
                     '\\' => string.push('\\'),
                     't' => string.push('\t'),
                     'n' => string.push('\n'),
+                    '{' => string.push('{'),
+                    '}' => string.push('}'),
                     _ => return Err(error),
                 };
                 iter.next();
             }
+            '{' => {
+                if !string.is_empty() {
+                    parts.push(vec![vec![Ast::String(string)]]);
+                    string = String::new();
+                }
+                parts.push(load_block(iter, '{', '}')?);
+            }
             _ => {
                 string.push(c);
                 iter.next();
@@ -396,6 +633,69 @@ fn double_char(
     Ok(return_types.0)
 }
 
+fn char_or_equals(
+    iter: &mut Peekable<Chars>,
+    to_match: char,
+    error: ParserError,
+    return_types: (Ast, Ast),
+) -> Result<Ast, ParserError> {
+    let c = peek_next_char(iter, &error)?;
+    if c == to_match {
+        iter.next();
+    } else {
+        return Err(error);
+    }
+    let c2 = peek_next_char(iter, &error)?;
+    if c2 == '=' {
+        iter.next();
+        return Ok(return_types.1);
+    }
+    Ok(return_types.0)
+}
+
+// > and >> are io writes so a strict greater than is written as >?
+fn load_greater(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
+    let error = ParserError::InvalidIoWrite;
+    let c = peek_next_char(iter, &error)?;
+    if c == '>' {
+        iter.next();
+    } else {
+        return Err(error);
+    }
+    let error = ParserError::InvalidGreaterThan;
+    let c2 = peek_next_char(iter, &error)?;
+    match c2 {
+        '>' => {
+            iter.next();
+            Ok(Ast::IoAppend)
+        }
+        '=' => {
+            iter.next();
+            Ok(Ast::GreaterEquals)
+        }
+        '?' => {
+            iter.next();
+            Ok(Ast::GreaterThan)
+        }
+        _ => Ok(Ast::IoWrite),
+    }
+}
+
+fn load_not_equals(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
+    let error = ParserError::InvalidNotEquals;
+    let c = peek_next_char(iter, &error)?;
+    if c == '!' {
+        iter.next();
+    } else {
+        return Err(error);
+    }
+    if let Some(&'=') = iter.peek() {
+        iter.next();
+        return Ok(Ast::NotEquals);
+    }
+    Ok(Ast::Not)
+}
+
 fn load_equals(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
     let error = ParserError::InvalidAssign;
     let c = peek_next_char(iter, &error)?;
@@ -408,12 +708,18 @@ fn load_equals(iter: &mut Peekable<Chars>) -> Result<Ast, ParserError> {
     let c2 = peek_next_char(iter, &error)?;
     if c2 == '=' {
         iter.next();
+        if let Some(&'=') = iter.peek() {
+            iter.next();
+            return Ok(Ast::Identical);
+        }
         return Ok(Ast::Equals);
     } else if c2 == '[' {
-        return Ok(Ast::AccessAssign(
-            load_block(iter, '[', ']')?,
-            load_til_end(iter, "\n;")?,
-        ));
+        let mut parts = load_access(iter)?;
+        let assign_body = load_til_end(iter, "\n;}")?;
+        if parts.len() == 1 {
+            return Ok(Ast::AccessAssign(parts.pop().unwrap(), assign_body));
+        }
+        return Ok(Ast::SliceAssign(parts, assign_body));
     }
-    Ok(Ast::Assign(load_til_end(iter, "\n;")?))
+    Ok(Ast::Assign(load_til_end(iter, "\n;}")?))
 }
@@ -1,64 +1,154 @@
 use std::rc::Rc;
 use std::cell::RefCell;
-use super::super::command::Command;
-use super::super::data_type::DataType;
+use super::super::command::{Capture, Command, SharedNames};
+use super::super::data_type::{Arity, DataType};
 use super::super::error::ParserError;
-use super::ast::{Ast, AstArgs, AstBody};
+use super::ast::{named_arg, Ast, AstArgs, AstBody, AstPattern, AstSlice, AstTry};
 
 pub fn load_commands_from_ast(ast: &Vec<Ast>) -> Result<Vec<Command>, ParserError> {
     let mut new_commands = Vec::new();
+    // where the commands for each ast start
+    let mut command_starts = Vec::new();
     let mut current_index = 0;
 
     while current_index < ast.len() {
+        command_starts.push(new_commands.len());
         if let Some(assign_body) = ast[current_index].is_assign() {
             let total_look_back = ast[current_index].num_look_back();
             can_look_back(current_index, total_look_back)?;
-            new_commands.append(&mut load_body(assign_body)?);
-            let save_cmd = match ast[current_index - 1] {
-                Ast::VarLocal(_, id) => Command::SaveLocal(id),
-                Ast::VarArg(_, id) => Command::SaveStackArg(id),
-                _ => {
-                    return Err(ParserError::CannotSaveFromAst(
-                        ast[current_index - 1].clone(),
-                    ))
-                }
-            };
-            new_commands.push(save_cmd);
+            new_commands.append(&mut load_value(assign_body)?);
+            new_commands.push(save_command(&ast[current_index - 1])?);
+        } else if let Some((pattern, body)) = ast[current_index].is_destructure() {
+            new_commands.append(&mut load_value(body)?);
+            new_commands.append(&mut build_pattern(pattern)?);
+        } else if let Some(parts) = ast[current_index].is_interpolate() {
+            new_commands.append(&mut build_interpolate(parts)?);
         } else if let Some(array_items) = ast[current_index].is_array() {
             let mut array_commands = build_array(array_items)?;
             new_commands.append(&mut array_commands);
+        } else if let Some(dict_items) = ast[current_index].is_dict() {
+            let mut dict_commands = build_dict(dict_items)?;
+            new_commands.append(&mut dict_commands);
+        } else if let Some(has_body) = ast[current_index].is_has() {
+            new_commands.append(&mut load_body(has_body)?);
+            new_commands.push(Command::Has);
+        } else if let Some(remove_body) = ast[current_index].is_remove() {
+            if remove_body.is_empty() {
+                new_commands.push(Command::Pop);
+            } else {
+                new_commands.append(&mut load_body(remove_body)?);
+                new_commands.push(Command::Remove);
+            }
+        } else if let Some(parts) = ast[current_index].is_remove_slice() {
+            new_commands.append(&mut build_slice(parts, None, Command::RemoveSlice)?);
+        } else if let Some((index_body, value_body)) = ast[current_index].is_insert() {
+            new_commands.append(&mut load_value(index_body)?);
+            new_commands.append(&mut load_value(value_body)?);
+            if index_body.is_empty() {
+                new_commands.push(Command::Push);
+            } else {
+                new_commands.push(Command::Insert);
+            }
         } else if let Some(access_body) = ast[current_index].is_access() {
             new_commands.append(&mut load_body(access_body)?);
             new_commands.push(Command::Access);
         } else if let Some((access_body, assign_body)) = ast[current_index].is_access_assign() {
-            new_commands.append(&mut load_body(access_body)?);
-            new_commands.append(&mut load_body(assign_body)?);
+            new_commands.append(&mut load_value(access_body)?);
+            new_commands.append(&mut load_value(assign_body)?);
             new_commands.push(Command::AccessAssign);
+        } else if let Some(parts) = ast[current_index].is_slice() {
+            new_commands.append(&mut build_slice(parts, None, Command::Slice)?);
+        } else if let Some((parts, assign_body)) = ast[current_index].is_slice_assign() {
+            new_commands.append(&mut build_slice(parts, Some(assign_body), Command::SliceAssign)?);
         } else if let Some((access_body, args)) = ast[current_index].is_access_call() {
-            new_commands.append(&mut build_function_call(args)?);
+            let (mut call_commands, names) = build_function_call(args)?;
+            new_commands.append(&mut call_commands);
             if current_index > 0 && ast[current_index - 1].can_call() {
                 new_commands.push(ast_to_command(&ast[current_index - 1])?);
                 new_commands.append(&mut load_body(access_body)?);
                 new_commands.push(Command::Access);
-                new_commands.push(Command::Call);
+                new_commands.push(Command::Call(args.len(), names));
             } else {
                 return Err(ParserError::InvalidAccessCall);
             }
         } else if let Some(args) = ast[current_index].is_function_call() {
-            new_commands.append(&mut build_function_call(args)?);
+            let (mut call_commands, names) = build_function_call(args)?;
+            new_commands.append(&mut call_commands);
             new_commands.push(ast_to_command(&ast[current_index - 1])?);
-            new_commands.push(Command::Call);
+            new_commands.push(Command::Call(args.len(), names));
         } else if let Some(args) = ast[current_index].is_function_self_call() {
-            new_commands.append(&mut build_function_call(args)?);
-            new_commands.push(Command::CallSelf);
+            let (mut call_commands, names) = build_function_call(args)?;
+            new_commands.append(&mut call_commands);
+            new_commands.push(Command::CallSelf(args.len(), names));
         } else if let Some(if_body) = ast[current_index].is_if() {
             let mut total_if_commands = load_body(if_body)?;
             // add jump command
             new_commands.push(Command::JumpIfFalse(total_if_commands.len() + 1));
             new_commands.append(&mut total_if_commands);
+        } else if let Some((if_body, else_body)) = ast[current_index].is_if_else() {
+            let mut total_if_commands = load_body(if_body)?;
+            let mut total_else_commands = load_body(else_body)?;
+            new_commands.push(Command::JumpIfFalse(total_if_commands.len() + 2));
+            new_commands.append(&mut total_if_commands);
+            // skip the else when the if body is run
+            new_commands.push(Command::Jump(total_else_commands.len() + 1));
+            new_commands.append(&mut total_else_commands);
+        } else if let Some((while_body, slots)) = ast[current_index].is_while() {
+            let condition_start = command_starts[operand_start(ast, current_index)?];
+            let total_condition_commands = new_commands.len() - condition_start;
+            let stack_slot = match slots.first() {
+                Some(stack_slot) => stack_slot,
+                None => return Err(ParserError::InvalidWhile),
+            };
+            let mut mark_commands = vec![Command::StackLen, save_command(stack_slot)?];
+            let total_mark = mark_commands.len();
+            let mut total_while_commands = load_body(while_body)?;
+            let discard_commands = vec![ast_to_command(stack_slot)?, Command::TruncateStack];
+            let total_body = total_while_commands.len();
+            let total_discard = discard_commands.len();
+            // a break drops what the body left, a continue drops it and reruns the condition
+            patch_loop_controls(
+                &mut total_while_commands,
+                |index| Command::Jump(total_body - index + total_discard + 1),
+                |index| Command::Jump(total_body - index),
+            );
+            new_commands.push(Command::JumpIfFalse(
+                total_mark + total_body + total_discard * 2 + 2,
+            ));
+            new_commands.append(&mut mark_commands);
+            new_commands.append(&mut total_while_commands);
+            new_commands.append(&mut discard_commands.clone());
+            // rerun the condition
+            new_commands.push(Command::JumpBack(
+                total_condition_commands + 1 + total_mark + total_body + total_discard,
+            ));
+            new_commands.append(&mut discard_commands.clone());
+        } else if let Some((vars, each_body, slots)) = ast[current_index].is_each() {
+            new_commands.append(&mut build_each(vars, each_body, slots)?);
+        } else if let Some((blocks, slots)) = ast[current_index].is_try() {
+            new_commands.append(&mut build_try(blocks, slots)?);
+        } else if let Some((args, body, upvalues)) = ast[current_index].is_closure() {
+            new_commands.append(&mut build_closure(args, body, upvalues)?);
         } else if let Some(group_body) = ast[current_index].is_group() {
-            let mut group_commands = load_body(group_body)?;
+            let mut group_commands = load_value(group_body)?;
             new_commands.append(&mut group_commands);
+        } else if ast[current_index].is_logic() {
+            // the right side is only run when the left side does not decide the result
+            let right_index = operand_start(ast, current_index)?;
+            operand_start(ast, right_index)?;
+            let mut right_commands = new_commands.split_off(command_starts[right_index]);
+            let total_right_commands = right_commands.len();
+            if let Ast::And = ast[current_index] {
+                new_commands.push(Command::JumpIfFalse(total_right_commands + 2));
+                new_commands.append(&mut right_commands);
+                new_commands.push(Command::Jump(2));
+                new_commands.push(Command::PushStack(DataType::Bool(false)));
+            } else {
+                new_commands.push(Command::JumpIfFalse(3));
+                new_commands.push(Command::PushStack(DataType::Bool(true)));
+                new_commands.push(Command::Jump(total_right_commands + 1));
+                new_commands.append(&mut right_commands);
+            }
         } else if !(current_index + 1 < ast.len() && ast[current_index].is_var()
             && (ast[current_index + 1].is_assign().is_some()
                 || ast[current_index + 1].is_function_call().is_some()
@@ -80,6 +170,42 @@ fn load_body(body: &AstBody) -> Result<Vec<Command>, ParserError> {
     Ok(commands)
 }
 
+// the last value of the body is used
+fn load_value(body: &AstBody) -> Result<Vec<Command>, ParserError> {
+    if let Some(statement) = body.last() {
+        check_value(statement)?;
+    }
+    load_body(body)
+}
+
+// an if without an else gives nothing when it is false so it cannot be used as a value,
+// nor can an else if chain that ends without one
+fn check_value(statement: &[Ast]) -> Result<(), ParserError> {
+    match statement.last() {
+        Some(&Ast::If(_)) => Err(ParserError::IfWithoutElseAsValue),
+        Some(Ast::IfElse(body, else_body)) => {
+            for branch in [body, else_body].iter() {
+                if let Some(branch_statement) = branch.last() {
+                    check_value(branch_statement)?;
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn save_command(ast: &Ast) -> Result<Command, ParserError> {
+    let cmd = match *ast {
+        Ast::VarLocal(_, id) => Command::SaveLocal(id),
+        Ast::VarArg(_, id) => Command::SaveStackArg(id),
+        Ast::VarUpvalue(_, id) => Command::SaveUpvalue(id),
+        Ast::VarGlobal(_, id) => Command::SaveGlobal(id),
+        _ => return Err(ParserError::CannotSaveFromAst(ast.clone())),
+    };
+    Ok(cmd)
+}
+
 fn can_look_back(mut current_index: usize, mut total_look_back: usize) -> Result<(), ParserError> {
     while total_look_back > 0 {
         if current_index == 0 {
@@ -94,6 +220,21 @@ fn can_look_back(mut current_index: usize, mut total_look_back: usize) -> Result
     Ok(())
 }
 
+// index of the first ast making up the value used by the ast at index
+fn operand_start(ast: &[Ast], index: usize) -> Result<usize, ParserError> {
+    let mut needed = 1;
+    let mut current_index = index;
+    while current_index > 0 {
+        current_index -= 1;
+        let (pops, pushes) = ast[current_index].stack_effect();
+        needed = needed + pops - pushes;
+        if needed == 0 {
+            return Ok(current_index);
+        }
+    }
+    Err(ParserError::MissingOperand(ast[index].clone()))
+}
+
 fn ast_to_command(ast: &Ast) -> Result<Command, ParserError> {
     if ast.is_data() {
         return Ok(Command::PushStack(ast_to_data_type(ast)?));
@@ -101,9 +242,23 @@ fn ast_to_command(ast: &Ast) -> Result<Command, ParserError> {
     let cmd = match *ast {
         Ast::VarArg(_, id) => Command::LoadStackArg(id),
         Ast::VarLocal(_, id) => Command::LoadLocal(id),
+        Ast::VarUpvalue(_, id) => Command::LoadUpvalue(id),
+        Ast::VarGlobal(_, id) => Command::LoadGlobal(id),
+        Ast::Module(slot, ref commands) => Command::Import(slot, Rc::clone(commands)),
         Ast::Len => Command::Len,
         Ast::Return => Command::Return,
+        Ast::Throw => Command::Throw,
+        Ast::Break => Command::Break,
+        Ast::Continue => Command::Continue,
         Ast::Equals => Command::Equals,
+        Ast::NotEquals => Command::NotEquals,
+        Ast::Identical => Command::Identical,
+        Ast::LessThan => Command::LessThan,
+        Ast::LessEquals => Command::LessEquals,
+        Ast::GreaterThan => Command::GreaterThan,
+        Ast::GreaterEquals => Command::GreaterEquals,
+        Ast::Not => Command::Not,
+        Ast::Neg => Command::Neg,
         Ast::Add => Command::Add,
         Ast::Concat => Command::Concat,
         Ast::Sub => Command::Sub,
@@ -122,47 +277,378 @@ fn ast_to_data_type(ast: &Ast) -> Result<DataType, ParserError> {
     let dt = match *ast {
         Ast::Bool(b) => DataType::Bool(b),
         Ast::Integer(int) => DataType::Integer(int),
+        Ast::BigInt(ref big) => DataType::BigInt(big.clone()),
         Ast::Float(float) => DataType::Float(float),
         Ast::Char(c) => DataType::Char(c),
         Ast::String(ref string) => DataType::String(Rc::new(RefCell::new(string.clone()))),
-        Ast::Function(ref args, ref body) => {
-            let num_args = args.len();
-            let mut function_commands = Vec::new();
-            for body_part in body.iter() {
-                let mut sub_commands = load_commands_from_ast(body_part)?;
-                function_commands.append(&mut sub_commands);
+        Ast::Function(ref args, ref body) => build_function(args, body)?,
+        _ => return Err(ParserError::CannotConvetAstToDataType(ast.clone())),
+    };
+    Ok(dt)
+}
+
+pub fn build_each(
+    vars: &AstArgs,
+    body: &AstBody,
+    slots: &[Ast],
+) -> Result<Vec<Command>, ParserError> {
+    let mut var_asts = Vec::new();
+    for var in vars.iter() {
+        match var.first() {
+            Some(statement) if var.len() == 1 && statement.len() == 1 => {
+                var_asts.push(&statement[0])
             }
-            let mut add_return = false;
-            if let Some(cmd) = function_commands.last() {
-                if !cmd.is_return() {
-                    add_return = true;
-                }
+            _ => return Err(ParserError::InvalidEach),
+        };
+    }
+    if var_asts.is_empty() || var_asts.len() > 2 || slots.len() != 3 {
+        return Err(ParserError::InvalidEach);
+    }
+    let (collection, index, stack_len) = (&slots[0], &slots[1], &slots[2]);
+
+    let mut each_commands = vec![
+        Command::Iterable,
+        save_command(collection)?,
+        Command::PushStack(DataType::Integer(0)),
+        save_command(index)?,
+        Command::StackLen,
+        save_command(stack_len)?,
+    ];
+    let mut check_commands = vec![
+        ast_to_command(index)?,
+        ast_to_command(collection)?,
+        Command::Len,
+        Command::LessThan,
+    ];
+    let mut var_commands = vec![
+        ast_to_command(collection)?,
+        ast_to_command(index)?,
+        Command::Access,
+    ];
+    var_commands.append(&mut bind_commands(var_asts[0])?);
+    if let Some(index_var) = var_asts.get(1) {
+        var_commands.push(ast_to_command(index)?);
+        var_commands.append(&mut bind_commands(index_var)?);
+    }
+    let mut body_commands = load_body(body)?;
+    let total_body = body_commands.len();
+    // what the body leaves on the stack is dropped after each run and when the loop is left
+    let discard_commands = vec![ast_to_command(stack_len)?, Command::TruncateStack];
+    let total_discard = discard_commands.len();
+    let mut step_commands = vec![
+        ast_to_command(index)?,
+        Command::PushStack(DataType::Integer(1)),
+        Command::Add,
+        save_command(index)?,
+    ];
+    patch_loop_controls(
+        &mut body_commands,
+        |index| Command::Jump(total_body - index + total_discard + step_commands.len() + 1),
+        |index| Command::Jump(total_body - index),
+    );
+
+    // from the length check through the jump back to it
+    let total_loop = check_commands.len() + 1 + var_commands.len() + total_body + total_discard
+        + step_commands.len() + 1;
+    let exit_jump = Command::JumpIfFalse(total_loop - check_commands.len());
+    check_commands.push(exit_jump);
+    each_commands.append(&mut check_commands);
+    each_commands.append(&mut var_commands);
+    each_commands.append(&mut body_commands);
+    each_commands.append(&mut discard_commands.clone());
+    each_commands.append(&mut step_commands);
+    each_commands.push(Command::JumpBack(total_loop - 1));
+    each_commands.append(&mut discard_commands.clone());
+    Ok(each_commands)
+}
+
+// each run of a loop gets new vars so the closures made in a run keep its values
+fn bind_commands(var: &Ast) -> Result<Vec<Command>, ParserError> {
+    let mut commands = Vec::new();
+    if let Ast::VarLocal(_, id) = *var {
+        commands.push(Command::UnshareLocal(id));
+    }
+    commands.push(save_command(var)?);
+    Ok(commands)
+}
+
+pub fn build_try(blocks: &AstTry, slots: &[Ast]) -> Result<Vec<Command>, ParserError> {
+    let (ref body, ref catch, ref finally) = *blocks;
+    let (error_slot, stack_slot) = match *slots {
+        [ref error_slot, ref stack_slot] => (error_slot, stack_slot),
+        _ => return Err(ParserError::InvalidTry),
+    };
+    let mut try_commands = load_body(body)?;
+
+    if let Some((ref vars, ref catch_body)) = *catch {
+        let error_var = match vars.len() {
+            0 => error_slot,
+            1 => match vars[0].first() {
+                Some(statement) if vars[0].len() == 1 && statement.len() == 1 => &statement[0],
+                _ => return Err(ParserError::InvalidTry),
+            },
+            _ => return Err(ParserError::InvalidTry),
+        };
+        // leaving the try block removes its handler, the catch is run after it is removed
+        let body_commands = leave_through(try_commands, |control| {
+            vec![Command::TryEnd, control.clone()]
+        });
+        let mut catch_commands = vec![save_command(error_var)?];
+        catch_commands.append(&mut load_body(catch_body)?);
+        let mut commands = vec![Command::TryStart(body_commands.len() + 3)];
+        commands.extend(body_commands);
+        commands.push(Command::TryEnd);
+        commands.push(Command::Jump(catch_commands.len() + 1));
+        commands.append(&mut catch_commands);
+        try_commands = commands;
+    }
+
+    let finally_body = match *finally {
+        Some(ref finally_body) => finally_body,
+        None => return Ok(try_commands),
+    };
+    // the finally is run once when nothing is thrown, once before passing on what was
+    // and once for each break, continue or return leaving the try or catch
+    let mut finally_commands = load_body(finally_body)?;
+    let total_finally = finally_commands.len();
+    // what the finally leaves on the stack is dropped so a return gives back its own value
+    let mark_commands = [Command::StackLen, save_command(stack_slot)?];
+    let discard_commands = [ast_to_command(stack_slot)?, Command::TruncateStack];
+    let try_commands = leave_through(try_commands, |control| {
+        let mut exit_commands = Vec::new();
+        if control.is_return() {
+            exit_commands.extend(mark_commands.iter().cloned());
+        }
+        exit_commands.push(Command::TryEnd);
+        exit_commands.extend(finally_commands.iter().cloned());
+        if control.is_return() {
+            exit_commands.extend(discard_commands.iter().cloned());
+        }
+        exit_commands.push(control.clone());
+        exit_commands
+    });
+    let mut commands = vec![Command::TryStart(try_commands.len() + total_finally + 3)];
+    commands.extend(try_commands);
+    commands.push(Command::TryEnd);
+    commands.append(&mut finally_commands.clone());
+    commands.push(Command::Jump(total_finally + 4));
+    commands.push(save_command(error_slot)?);
+    commands.append(&mut finally_commands);
+    commands.push(ast_to_command(error_slot)?);
+    commands.push(Command::Throw);
+    Ok(commands)
+}
+
+// send each break, continue and return in the commands through the exit made for it,
+// the exits are put after the commands with a jump over them
+fn leave_through<F>(mut commands: Vec<Command>, exit: F) -> Vec<Command>
+where
+    F: Fn(&Command) -> Vec<Command>,
+{
+    let total = commands.len();
+    let mut exits = Vec::new();
+    for (index, command) in commands.iter_mut().enumerate() {
+        if !command.is_loop_control() && !command.is_return() {
+            continue;
+        }
+        let mut exit_commands = exit(command);
+        *command = Command::Jump(total - index + 1 + exits.len());
+        exits.append(&mut exit_commands);
+    }
+    if exits.is_empty() {
+        return commands;
+    }
+    commands.push(Command::Jump(exits.len() + 1));
+    commands.append(&mut exits);
+    commands
+}
+
+// turn the breaks and continues of a loop body into jumps from their index in the body
+fn patch_loop_controls<B, C>(body: &mut [Command], on_break: B, on_continue: C)
+where
+    B: Fn(usize) -> Command,
+    C: Fn(usize) -> Command,
+{
+    for (index, command) in body.iter_mut().enumerate() {
+        let patched = match *command {
+            Command::Break => on_break(index),
+            Command::Continue => on_continue(index),
+            _ => continue,
+        };
+        *command = patched;
+    }
+}
+
+pub fn check_loop_controls(commands: &[Command]) -> Result<(), ParserError> {
+    if commands.iter().any(|command| command.is_loop_control()) {
+        return Err(ParserError::LoopControlOutsideLoop);
+    }
+    Ok(())
+}
+
+// calls with nothing left to run after them reuse the frame of the function
+fn mark_tail_calls(commands: &mut [Command]) {
+    for index in 0..commands.len() {
+        if !returns_from(commands, index + 1) {
+            continue;
+        }
+        commands[index] = match commands[index] {
+            Command::Call(given, ref names) => Command::TailCall(given, Rc::clone(names)),
+            Command::CallSelf(given, ref names) => Command::TailCallSelf(given, Rc::clone(names)),
+            _ => continue,
+        };
+    }
+}
+
+// follow the jumps from the index to see if the next command run is a return
+fn returns_from(commands: &[Command], mut index: usize) -> bool {
+    while let Some(command) = commands.get(index) {
+        match *command {
+            Command::Return => return true,
+            Command::Jump(to) => index += to,
+            _ => return false,
+        }
+    }
+    false
+}
+
+fn build_function(args: &AstArgs, body: &AstBody) -> Result<DataType, ParserError> {
+    let (arity, mut function_commands) = build_arity(args)?;
+    for body_part in body.iter() {
+        let mut sub_commands = load_commands_from_ast(body_part)?;
+        function_commands.append(&mut sub_commands);
+    }
+    let mut add_return = false;
+    if let Some(cmd) = function_commands.last() {
+        if !cmd.is_return() {
+            add_return = true;
+        }
+    }
+    if add_return {
+        function_commands.push(Command::Return);
+    }
+    check_loop_controls(&function_commands)?;
+    mark_tail_calls(&mut function_commands);
+    Ok(DataType::Function(Rc::new(function_commands), Rc::new(arity)))
+}
+
+fn is_rest_arg(arg: &[Vec<Ast>]) -> bool {
+    if let Some(&Ast::Rest) = arg.first().and_then(|statement| statement.first()) {
+        return true;
+    }
+    false
+}
+
+// the args the function takes and the commands that set the ones left out to their defaults,
+// only the last arg can be a rest arg
+fn build_arity(args: &AstArgs) -> Result<(Arity, Vec<Command>), ParserError> {
+    let mut arity = Arity::new();
+    let mut default_commands = Vec::new();
+    for (index, arg) in args.iter().enumerate() {
+        if is_rest_arg(arg) {
+            if index + 1 != args.len() {
+                return Err(ParserError::InvalidRest);
             }
-            if add_return {
-                function_commands.push(Command::Return);
+            arity.rest = true;
+        } else if let Some((name, value)) = named_arg(arg) {
+            let mut default = load_value(value)?;
+            default_commands.push(Command::ArgMissing(index));
+            default_commands.push(Command::JumpIfFalse(default.len() + 2));
+            default_commands.append(&mut default);
+            default_commands.push(Command::SaveStackArg(index));
+            arity.names.push(name.to_string());
+            arity.defaults.push(true);
+        } else if let [ref statement] = **arg {
+            if let [Ast::VarArg(ref name, _)] = statement.as_slice() {
+                arity.names.push(name.clone());
+                arity.defaults.push(false);
+                continue;
             }
-            DataType::Function(Rc::new(function_commands), num_args)
+            return Err(ParserError::InvalidFunction);
+        } else {
+            return Err(ParserError::InvalidFunction);
         }
-        _ => return Err(ParserError::CannotConvetAstToDataType(ast.clone())),
+    }
+    Ok((arity, default_commands))
+}
+
+// the value being taken apart is on the stack
+fn build_pattern(pattern: &AstPattern) -> Result<Vec<Command>, ParserError> {
+    let (items, slot) = match *pattern {
+        AstPattern::Var(ref var) => return Ok(vec![save_command(var)?]),
+        AstPattern::Rest(_) => return Err(ParserError::InvalidPattern),
+        AstPattern::Nested(ref items, ref slot) => (items, slot),
     };
-    Ok(dt)
+    let rest = matches!(items.last(), Some(&AstPattern::Rest(_)));
+    let mut pattern_commands = vec![
+        save_command(slot)?,
+        ast_to_command(slot)?,
+        Command::MatchLength(items.len() - rest as usize, rest),
+    ];
+    for (index, item) in items.iter().enumerate() {
+        pattern_commands.push(ast_to_command(slot)?);
+        if let AstPattern::Rest(ref var) = *item {
+            pattern_commands.push(Command::RestFrom(index));
+            pattern_commands.push(save_command(var)?);
+            continue;
+        }
+        pattern_commands.push(Command::PushStack(DataType::Integer(index as i64)));
+        pattern_commands.push(Command::Access);
+        pattern_commands.append(&mut build_pattern(item)?);
+    }
+    Ok(pattern_commands)
+}
+
+// the function is created at runtime sharing the vars it captures
+pub fn build_closure(
+    args: &AstArgs,
+    body: &AstBody,
+    upvalues: &[Ast],
+) -> Result<Vec<Command>, ParserError> {
+    let mut captures = Vec::new();
+    for upvalue in upvalues.iter() {
+        let capture = match *upvalue {
+            Ast::VarArg(_, id) => Capture::Arg(id),
+            Ast::VarLocal(_, id) => Capture::Local(id),
+            Ast::VarUpvalue(_, id) => Capture::Upvalue(id),
+            _ => return Err(ParserError::CannotConvertAstToCommand(upvalue.clone())),
+        };
+        captures.push(capture);
+    }
+    Ok(vec![
+        Command::PushStack(build_function(args, body)?),
+        Command::InitClosure(Rc::new(captures)),
+    ])
 }
 
-pub fn build_function_call(args: &AstArgs) -> Result<Vec<Command>, ParserError> {
+// args given by name come after all the others
+pub fn build_function_call(args: &AstArgs) -> Result<(Vec<Command>, SharedNames), ParserError> {
     let mut call_commands = Vec::new();
+    let mut names = Vec::new();
     for arg in args.iter() {
+        if let Some((name, value)) = named_arg(arg) {
+            call_commands.append(&mut load_value(value)?);
+            names.push(name.to_string());
+            continue;
+        }
+        if !names.is_empty() {
+            return Err(ParserError::InvalidNamedArg);
+        }
         for arg_group in arg.iter() {
+            check_value(arg_group)?;
             let mut arg_commands = load_commands_from_ast(arg_group)?;
             call_commands.append(&mut arg_commands);
         }
     }
-    Ok(call_commands)
+    Ok((call_commands, Rc::new(names)))
 }
 
+
 pub fn build_array(items: &AstArgs) -> Result<Vec<Command>, ParserError> {
     let mut array_commands = vec![Command::InitArray];
     for item in items.iter() {
         for item_group in item.iter() {
+            check_value(item_group)?;
             let mut item_commands = load_commands_from_ast(item_group)?;
             array_commands.append(&mut item_commands);
             array_commands.push(Command::ArrayPush);
@@ -170,3 +656,46 @@ pub fn build_array(items: &AstArgs) -> Result<Vec<Command>, ParserError> {
     }
     Ok(array_commands)
 }
+
+pub fn build_interpolate(parts: &AstArgs) -> Result<Vec<Command>, ParserError> {
+    let mut string_commands = vec![Command::InitString];
+    for part in parts.iter() {
+        for part_group in part.iter() {
+            check_value(part_group)?;
+            string_commands.append(&mut load_commands_from_ast(part_group)?);
+            string_commands.push(Command::StringPush);
+        }
+    }
+    Ok(string_commands)
+}
+
+// only the parts that are given are pushed, then the value to assign if any
+fn build_slice(
+    parts: &AstSlice,
+    assign_body: Option<&AstBody>,
+    slice_command: fn(bool, bool, bool) -> Command,
+) -> Result<Vec<Command>, ParserError> {
+    let mut slice_commands = Vec::new();
+    let mut given = Vec::new();
+    for part in parts.iter() {
+        let mut part_commands = load_body(part)?;
+        given.push(!part_commands.is_empty());
+        slice_commands.append(&mut part_commands);
+    }
+    given.resize(3, false);
+    if let Some(assign_body) = assign_body {
+        slice_commands.append(&mut load_value(assign_body)?);
+    }
+    slice_commands.push(slice_command(given[0], given[1], given[2]));
+    Ok(slice_commands)
+}
+
+pub fn build_dict(items: &[(AstBody, AstBody)]) -> Result<Vec<Command>, ParserError> {
+    let mut dict_commands = vec![Command::InitDict];
+    for (key, value) in items.iter() {
+        dict_commands.append(&mut load_value(key)?);
+        dict_commands.append(&mut load_value(value)?);
+        dict_commands.push(Command::DictInsert);
+    }
+    Ok(dict_commands)
+}
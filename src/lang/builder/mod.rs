@@ -1,6 +1,7 @@
 mod ast;
 mod symbol;
 mod builder;
+mod module;
 
 use std::str::Chars;
 use std::iter::Peekable;
@@ -9,7 +10,7 @@ use super::command::{Command, SharedCommands};
 use super::error::ParserError;
 use self::ast::{load_ast_til_end, shunt_yard};
 pub use self::symbol::SymbolTable;
-use self::builder::load_commands_from_ast;
+use self::builder::{check_loop_controls, load_commands_from_ast};
 use self::ast::AstBody;
 pub use self::ast::Ast;
 use super::util::{write_debug, KscriptDebug};
@@ -66,6 +67,10 @@ fn build_debug(
                         return Err(error);
                     }
                 };
+                if let Err(error) = check_loop_controls(&new_commands) {
+                    print_debug(&debug_ast, &debug_shunt_ast, &commands);
+                    return Err(error);
+                }
                 commands.append(&mut new_commands);
             }
         }
@@ -79,17 +84,26 @@ fn build(
     iter: &mut Peekable<Chars>,
     root_symbols: &mut SymbolTable,
 ) -> Result<SharedCommands, ParserError> {
+    let mut commands = build_statements(iter, root_symbols)?;
+    commands.push(Command::Halt(0));
+    Ok(Rc::new(commands))
+}
+
+fn build_statements(
+    iter: &mut Peekable<Chars>,
+    symbols: &mut SymbolTable,
+) -> Result<Vec<Command>, ParserError> {
     let mut commands = Vec::new();
     while iter.peek().is_some() {
         let mut ast = load_ast_til_end(iter)?;
         if ast.len() > 0 {
-            let shunt = shunt_yard(&mut ast, root_symbols)?;
+            let shunt = shunt_yard(&mut ast, symbols)?;
             if shunt.len() > 0 {
                 let mut new_commands = load_commands_from_ast(&shunt)?;
+                check_loop_controls(&new_commands)?;
                 commands.append(&mut new_commands);
             }
         }
     }
-    commands.push(Command::Halt(0));
-    Ok(Rc::new(commands))
+    Ok(commands)
 }
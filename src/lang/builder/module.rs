@@ -0,0 +1,114 @@
+use std::rc::Rc;
+use std::collections::HashMap;
+use std::mem;
+use std::path::{Path, PathBuf};
+use super::super::command::{Command, SharedCommands};
+use super::super::error::ParserError;
+use super::super::util::load_file_to_string;
+use super::symbol::SymbolTable;
+use super::build_statements;
+
+#[derive(Debug)]
+pub struct Modules {
+    search_paths: Vec<PathBuf>,
+    // files being built, the last one is doing the importing
+    loading: Vec<PathBuf>,
+    // namespace slot and commands of each file already built
+    loaded: HashMap<PathBuf, (usize, SharedCommands)>,
+}
+
+impl Modules {
+    pub fn new() -> Modules {
+        Modules {
+            search_paths: Vec::new(),
+            loading: Vec::new(),
+            loaded: HashMap::new(),
+        }
+    }
+
+    pub fn add_search_path(&mut self, path: &str) {
+        self.search_paths.push(PathBuf::from(path));
+    }
+
+    // imports in the file are found relative to it
+    pub fn start_file(&mut self, filename: &str) {
+        let path = Path::new(filename);
+        self.loading
+            .push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    }
+
+    pub fn end_file(&mut self) {
+        self.loading.pop();
+    }
+
+    // look next to the importing file then in each search path
+    fn find(&self, name: &str) -> Result<PathBuf, ParserError> {
+        let mut file = PathBuf::from(name);
+        if file.extension().is_none() {
+            file.set_extension("ks");
+        }
+        let importing_dir = match self.loading.last().and_then(|path| path.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::from("."),
+        };
+        let dirs = Some(&importing_dir)
+            .into_iter()
+            .chain(self.search_paths.iter());
+        for dir in dirs {
+            let path = dir.join(&file);
+            if path.is_file() {
+                return match path.canonicalize() {
+                    Ok(path) => Ok(path),
+                    Err(error) => Err(ParserError::FileLoadFile(format!("{:?}", error))),
+                };
+            }
+        }
+        Err(ParserError::ModuleNotFound(name.to_string()))
+    }
+}
+
+// build a file once giving the global slot its namespace is kept in and the commands that fill it
+pub fn load_module(
+    name: &str,
+    symbols: &mut SymbolTable,
+) -> Result<(usize, SharedCommands), ParserError> {
+    let path = symbols.modules.find(name)?;
+    if let Some(&(slot, ref commands)) = symbols.modules.loaded.get(&path) {
+        return Ok((slot, Rc::clone(commands)));
+    }
+    if symbols.modules.loading.contains(&path) {
+        return Err(ParserError::ImportCycle(path.display().to_string()));
+    }
+    let program = match load_file_to_string(&path.to_string_lossy()) {
+        Ok(program) => program,
+        Err(file_error) => return Err(ParserError::FileLoadFile(format!("{:?}", file_error))),
+    };
+
+    let modules = mem::replace(&mut symbols.modules, Modules::new());
+    let mut module_symbols =
+        SymbolTable::new_module(symbols.global_counter(), modules, symbols.builtins().clone());
+    module_symbols.modules.loading.push(path.clone());
+    let built = build_statements(&mut program.chars().peekable(), &mut module_symbols);
+    module_symbols.modules.loading.pop();
+    // always hand back the modules so the table can be reused after an error
+    mem::swap(&mut symbols.modules, &mut module_symbols.modules);
+    symbols.set_global_counter(module_symbols.global_counter());
+    let body = built?;
+
+    let slot = symbols.reserve_global();
+    // the namespace is made before the module runs and follows its top level names,
+    // so changes its functions make later are seen in it
+    let mut commands = vec![
+        Command::Export(Rc::new(module_symbols.globals())),
+        Command::SaveGlobal(slot),
+    ];
+    commands.extend(body);
+    commands.push(Command::LoadGlobal(slot));
+    commands.push(Command::Return);
+    let commands = Rc::new(commands);
+    symbols
+        .modules
+        .loaded
+        .insert(path, (slot, Rc::clone(&commands)));
+    Ok((slot, commands))
+}
@@ -1,49 +1,278 @@
 use std::collections::HashMap;
+use super::module::Modules;
 
 #[derive(Debug, Clone)]
 pub enum SymbolType {
     Arg(usize),
     Local(usize),
+    Upvalue(usize),
+    Global(usize),
 }
 
 #[derive(Debug)]
-pub struct SymbolTable {
+struct Scope {
     table: HashMap<String, SymbolType>,
     counter: usize,
     mode: SymbolType,
+    // name and where to load each captured value from in the enclosing scope
+    upvalues: Vec<(String, SymbolType)>,
+    // names of the args of the function
+    args: Vec<String>,
 }
 
-impl SymbolTable {
-    pub fn new() -> SymbolTable {
-        SymbolTable {
+impl Scope {
+    fn new() -> Scope {
+        Scope {
             table: HashMap::new(),
             counter: 0,
             mode: SymbolType::Local(0),
+            upvalues: Vec::new(),
+            args: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SymbolTable {
+    scopes: Vec<Scope>,
+    // the top level names of a module are globals of the program importing it
+    module: bool,
+    pub modules: Modules,
+    // global slots of the values given by rust, only found when a name is read and not defined
+    builtins: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable {
+            scopes: vec![Scope::new()],
+            module: false,
+            modules: Modules::new(),
+            builtins: HashMap::new(),
+        }
+    }
+
+    // a module shares the global slots so its names start after the ones in use
+    pub fn new_module(
+        global_counter: usize,
+        modules: Modules,
+        builtins: HashMap<String, usize>,
+    ) -> SymbolTable {
+        let mut root = Scope::new();
+        root.counter = global_counter;
+        SymbolTable {
+            scopes: vec![root],
+            module: true,
+            modules,
+            builtins,
         }
     }
 
+    pub fn builtins(&self) -> &HashMap<String, usize> {
+        &self.builtins
+    }
+
+    // the slot of a top level name or a builtin
+    pub fn global(&self, name: &str) -> Option<usize> {
+        if let Some(&SymbolType::Local(index)) = self.scopes[0].table.get(name) {
+            return Some(index);
+        }
+        self.builtins.get(name).cloned()
+    }
+
+    // the slot of a top level name being set from rust, a builtin of the same name is hidden
+    pub fn global_target(&mut self, name: &str) -> usize {
+        if let Some(&SymbolType::Local(index)) = self.scopes[0].table.get(name) {
+            return index;
+        }
+        let index = self.reserve_global();
+        self.scopes[0]
+            .table
+            .insert(name.to_string(), SymbolType::Local(index));
+        index
+    }
+
+    // the global slot for a builtin, adding one with the same name reuses its slot
+    pub fn add_builtin(&mut self, name: &str) -> usize {
+        if let Some(index) = self.builtins.get(name) {
+            return *index;
+        }
+        let index = self.reserve_global();
+        self.builtins.insert(name.to_string(), index);
+        index
+    }
+
+    pub fn global_counter(&self) -> usize {
+        self.scopes[0].counter
+    }
+
+    pub fn set_global_counter(&mut self, counter: usize) {
+        self.scopes[0].counter = counter;
+    }
+
+    pub fn reserve_global(&mut self) -> usize {
+        let index = self.scopes[0].counter;
+        self.scopes[0].counter += 1;
+        index
+    }
+
+    // the named top level slots of a module sorted by name
+    pub fn globals(&self) -> Vec<(String, usize)> {
+        let mut globals: Vec<(String, usize)> = self.scopes[0]
+            .table
+            .iter()
+            .filter_map(|(name, symbol_type)| match *symbol_type {
+                SymbolType::Local(index) => Some((name.clone(), index)),
+                _ => None,
+            })
+            .collect();
+        globals.sort();
+        globals
+    }
+
+    fn current(&mut self) -> &mut Scope {
+        self.scopes.last_mut().unwrap()
+    }
+
+    // start a function, names not found in it are looked up in the enclosing scopes
+    pub fn push_scope(&mut self) {
+        self.scopes.push(Scope::new());
+    }
+
+    // end a function returning what it captured
+    pub fn pop_scope(&mut self) -> Vec<(String, SymbolType)> {
+        if self.scopes.len() > 1 {
+            if let Some(scope) = self.scopes.pop() {
+                return scope.upvalues;
+            }
+        }
+        Vec::new()
+    }
+
+    // args and locals are indexed separately
     pub fn set_arg_mode(&mut self) {
-        self.mode = SymbolType::Arg(0);
+        let scope = self.current();
+        scope.mode = SymbolType::Arg(0);
+        scope.counter = 0;
     }
 
     pub fn set_local_mode(&mut self) {
-        self.mode = SymbolType::Local(0);
+        let scope = self.current();
+        scope.mode = SymbolType::Local(0);
+        scope.counter = 0;
+    }
+
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.current().args = args;
     }
 
-    pub fn set_counter(&mut self, counter: usize) {
-        self.counter = counter;
+    pub fn args(&self) -> &[String] {
+        match self.scopes.last() {
+            Some(scope) => &scope.args,
+            None => &[],
+        }
     }
 
+    // a name being read, in a function an unknown name is taken to be a global defined later
     pub fn get(&mut self, name: &str) -> SymbolType {
-        if let Some(symbol_type) = self.table.get(name) {
-            return symbol_type.clone();
+        let depth = self.scopes.len() - 1;
+        // args always belong to the function being built
+        if let SymbolType::Arg(_) = self.current().mode {
+            if let Some(symbol_type) = self.current().table.get(name) {
+                return symbol_type.clone();
+            }
+            return self.insert(name, depth);
+        }
+        if let Some(symbol_type) = self.resolve(name, depth) {
+            return self.globalize(depth, symbol_type);
+        }
+        if let Some(index) = self.builtins.get(name) {
+            return SymbolType::Global(*index);
+        }
+        if depth > 0 {
+            if let SymbolType::Local(index) = self.insert(name, 0) {
+                return SymbolType::Global(index);
+            }
+        }
+        let symbol_type = self.insert(name, depth);
+        self.globalize(depth, symbol_type)
+    }
+
+    // a name being assigned to, unknown names and globals belong to the current scope
+    pub fn get_target(&mut self, name: &str) -> SymbolType {
+        let depth = self.scopes.len() - 1;
+        let symbol_type = match self.resolve(name, depth) {
+            // a function only writes a global it names with global
+            Some(SymbolType::Global(_)) if depth > 0 => self.insert(name, depth),
+            Some(symbol_type) => symbol_type,
+            None => self.insert(name, depth),
+        };
+        self.globalize(depth, symbol_type)
+    }
+
+    // a name written global, always the top level slot even when a function has a local of the name
+    pub fn get_global(&mut self, name: &str) -> SymbolType {
+        let depth = self.scopes.len() - 1;
+        let symbol_type = match self.scopes[0].table.get(name) {
+            Some(symbol_type) => symbol_type.clone(),
+            None => self.insert(name, 0),
+        };
+        match symbol_type {
+            SymbolType::Local(index) if depth > 0 => SymbolType::Global(index),
+            _ => self.globalize(0, symbol_type),
+        }
+    }
+
+    fn globalize(&self, depth: usize, symbol_type: SymbolType) -> SymbolType {
+        match symbol_type {
+            SymbolType::Local(index) if self.module && depth == 0 => SymbolType::Global(index),
+            _ => symbol_type,
+        }
+    }
+
+    fn insert(&mut self, name: &str, depth: usize) -> SymbolType {
+        let symbol_type = self.reserve_in(depth);
+        self.scopes[depth]
+            .table
+            .insert(name.to_string(), symbol_type.clone());
+        symbol_type
+    }
+
+    // find a name in a scope or capture it from the scope around it
+    // names at the top level are globals and are never captured
+    fn resolve(&mut self, name: &str, depth: usize) -> Option<SymbolType> {
+        if let Some(symbol_type) = self.scopes[depth].table.get(name) {
+            return Some(symbol_type.clone());
         }
-        let symbol_type = match self.mode {
-            SymbolType::Arg(_) => SymbolType::Arg(self.counter),
-            SymbolType::Local(_) => SymbolType::Local(self.counter),
+        if depth == 0 {
+            return None;
+        }
+        let outer = match self.resolve(name, depth - 1)? {
+            SymbolType::Local(index) if depth == 1 => return Some(SymbolType::Global(index)),
+            SymbolType::Global(index) => return Some(SymbolType::Global(index)),
+            outer => outer,
+        };
+        let scope = &mut self.scopes[depth];
+        scope.upvalues.push((name.to_string(), outer));
+        let symbol_type = SymbolType::Upvalue(scope.upvalues.len() - 1);
+        scope.table.insert(name.to_string(), symbol_type.clone());
+        Some(symbol_type)
+    }
+
+    // a slot with no name for values the builder needs to keep
+    pub fn reserve(&mut self) -> SymbolType {
+        let depth = self.scopes.len() - 1;
+        let symbol_type = self.reserve_in(depth);
+        self.globalize(depth, symbol_type)
+    }
+
+    fn reserve_in(&mut self, depth: usize) -> SymbolType {
+        let scope = &mut self.scopes[depth];
+        let symbol_type = match scope.mode {
+            SymbolType::Arg(_) => SymbolType::Arg(scope.counter),
+            _ => SymbolType::Local(scope.counter),
         };
-        self.table.insert(name.to_string(), symbol_type.clone());
-        self.counter += 1;
+        scope.counter += 1;
         symbol_type
     }
 }
@@ -2,6 +2,17 @@ use std::rc::Rc;
 use super::data_type::DataType;
 
 pub type SharedCommands = Rc<Vec<Command>>;
+// names of the args given by name at a call site, they are the last args given
+pub type SharedNames = Rc<Vec<String>>;
+pub type SharedCaptures = Rc<Vec<Capture>>;
+
+// where a closure finds each var it captures in the function making it
+#[derive(Debug, Clone)]
+pub enum Capture {
+    Arg(usize),
+    Local(usize),
+    Upvalue(usize),
+}
 
 #[derive(Debug, Clone)]
 pub enum Command {
@@ -10,17 +21,61 @@ pub enum Command {
     // Locals
     SaveLocal(usize),
     LoadLocal(usize),
+    // stop sharing a local with the closures that captured it
+    UnshareLocal(usize),
+    // captured values of the running closure
+    SaveUpvalue(usize),
+    LoadUpvalue(usize),
+    SaveGlobal(usize),
+    LoadGlobal(usize),
+    // start a module namespace, saving to one of the named globals also sets it in the namespace
+    Export(Rc<Vec<(String, usize)>>),
+    // make a closure from the function, the vars it captures are shared with where they come from
+    InitClosure(SharedCaptures),
     // arrays
     InitArray,
     ArrayPush,
+    // strings built from interpolated parts
+    InitString,
+    StringPush,
+    // dicts
+    InitDict,
+    DictInsert,
+    // test for or remove a key, or remove at an index
+    Has,
+    Remove,
+    RemoveSlice(bool, bool, bool),
+    // add to the end or before an index
+    Push,
+    Insert,
+    Pop,
+    // what a loop runs over, the keys of a dict or the collection itself
+    Iterable,
+    // check the array has the number of items, or at least that many when there is a rest target
+    MatchLength(usize, bool),
+    // a new array of the items from the index on
+    RestFrom(usize),
     // Collection Info
     Len,
     // Collection Update
     Access,
+    // a new array or string from a slice, whether the start, end and step are given
+    Slice(bool, bool, bool),
+    SliceAssign(bool, bool, bool),
     AccessAssign,
     // Comparisons
     Equals,
+    NotEquals,
+    // the same value or the same shared reference
+    Identical,
+    LessThan,
+    LessEquals,
+    GreaterThan,
+    GreaterEquals,
+    // Logic
+    Not,
     // Math
+    Neg,
     Add,
     Sub,
     Mul,
@@ -30,11 +85,30 @@ pub enum Command {
     // Joins
     Concat,
     // Jumps all relative
+    Jump(usize),
+    JumpBack(usize),
     // if the top boolean in the stack is false
     JumpIfFalse(usize),
-    // Run function
-    Call,
-    CallSelf,
+    // push the length of the stack, then drop what was pushed after it so loops leave nothing behind
+    StackLen,
+    TruncateStack,
+    // Loop controls, replaced by jumps when the loop is built
+    Break,
+    Continue,
+    // Errors, a handler is the relative index of its catch
+    TryStart(usize),
+    TryEnd,
+    Throw,
+    // Run function with the number of args given
+    Call(usize, SharedNames),
+    CallSelf(usize, SharedNames),
+    // calls followed by a return, run in place of the current call
+    TailCall(usize, SharedNames),
+    TailCallSelf(usize, SharedNames),
+    // if the arg was left out of the call so its default is used
+    ArgMissing(usize),
+    // run a module the first time it is imported, its namespace is kept in the global slot
+    Import(usize, SharedCommands),
     // load an argument from the stack at an offset from the current function stack index
     SaveStackArg(usize),
     LoadStackArg(usize),
@@ -54,4 +128,8 @@ impl Command {
         }
         false
     }
+
+    pub fn is_loop_control(&self) -> bool {
+        matches!(*self, Command::Break | Command::Continue)
+    }
 }
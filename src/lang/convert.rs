@@ -0,0 +1,328 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+use super::data_type::{DataType, DictKey, NativeArgs, NativeFunction, SharedUserData, UserData};
+use super::error::RuntimeError;
+
+// a rust value a script can use, strings, arrays and dicts are new values
+pub trait IntoKscript {
+    fn into_kscript(self) -> DataType;
+}
+
+// a rust value made from a script value, a value of another type is a WrongType error
+pub trait FromKscript: Sized {
+    // an arg of this type can be left out of a call
+    const OPTIONAL: bool = false;
+
+    fn from_kscript(value: DataType) -> Result<Self, RuntimeError>;
+
+    // an arg that was left out is only given to an optional type
+    fn from_arg(value: Option<DataType>) -> Result<Self, RuntimeError> {
+        match value {
+            Some(value) => Self::from_kscript(value),
+            None => Err(RuntimeError::InvalidNumberOfArguments),
+        }
+    }
+}
+
+// what a native function gives back, None and () give back nothing like a script function can
+pub trait NativeReturn {
+    fn into_return(self) -> Result<Option<DataType>, RuntimeError>;
+}
+
+// a rust closure with typed args, each arg is converted before it is called
+pub trait IntoNative<Args> {
+    fn into_native(self, name: &str, args: &[&str]) -> NativeFunction;
+}
+
+impl NativeFunction {
+    // the names of the args are used for args given by name, there must be one for each closure arg
+    pub fn from_fn<F, Args>(name: &str, args: &[&str], callback: F) -> NativeFunction
+    where
+        F: IntoNative<Args>,
+    {
+        callback.into_native(name, args)
+    }
+}
+
+fn wrong_type(expected: &'static str, value: &DataType) -> RuntimeError {
+    RuntimeError::WrongType(expected, value.type_name())
+}
+
+fn new_string(string: String) -> DataType {
+    DataType::String(Rc::new(RefCell::new(string)))
+}
+
+impl IntoKscript for DataType {
+    fn into_kscript(self) -> DataType {
+        self
+    }
+}
+
+impl FromKscript for DataType {
+    fn from_kscript(value: DataType) -> Result<DataType, RuntimeError> {
+        Ok(value)
+    }
+}
+
+impl IntoKscript for bool {
+    fn into_kscript(self) -> DataType {
+        DataType::Bool(self)
+    }
+}
+
+impl FromKscript for bool {
+    fn from_kscript(value: DataType) -> Result<bool, RuntimeError> {
+        match value {
+            DataType::Bool(b) => Ok(b),
+            _ => Err(wrong_type("bool", &value)),
+        }
+    }
+}
+
+impl IntoKscript for i64 {
+    fn into_kscript(self) -> DataType {
+        DataType::Integer(self)
+    }
+}
+
+// a big integer does not fit
+impl FromKscript for i64 {
+    fn from_kscript(value: DataType) -> Result<i64, RuntimeError> {
+        match value {
+            DataType::Integer(int) => Ok(int),
+            DataType::BigInt(_) => Err(RuntimeError::Overflow),
+            _ => Err(wrong_type("int", &value)),
+        }
+    }
+}
+
+impl IntoKscript for f64 {
+    fn into_kscript(self) -> DataType {
+        DataType::Float(self)
+    }
+}
+
+// integers are widened the same as in math
+impl FromKscript for f64 {
+    fn from_kscript(value: DataType) -> Result<f64, RuntimeError> {
+        if value.is_number() {
+            return Ok(value.as_float());
+        }
+        Err(wrong_type("float", &value))
+    }
+}
+
+impl IntoKscript for char {
+    fn into_kscript(self) -> DataType {
+        DataType::Char(self)
+    }
+}
+
+impl FromKscript for char {
+    fn from_kscript(value: DataType) -> Result<char, RuntimeError> {
+        match value {
+            DataType::Char(c) => Ok(c),
+            _ => Err(wrong_type("char", &value)),
+        }
+    }
+}
+
+impl IntoKscript for String {
+    fn into_kscript(self) -> DataType {
+        new_string(self)
+    }
+}
+
+impl IntoKscript for &str {
+    fn into_kscript(self) -> DataType {
+        new_string(self.to_string())
+    }
+}
+
+// a single char is written the same as a string
+impl FromKscript for String {
+    fn from_kscript(value: DataType) -> Result<String, RuntimeError> {
+        match value {
+            DataType::Char(c) => Ok(c.to_string()),
+            DataType::String(ref string) => Ok(string.borrow().clone()),
+            _ => Err(wrong_type("string", &value)),
+        }
+    }
+}
+
+impl<T: IntoKscript> IntoKscript for Vec<T> {
+    fn into_kscript(self) -> DataType {
+        let items = self.into_iter().map(IntoKscript::into_kscript).collect();
+        DataType::Array(Rc::new(RefCell::new(items)))
+    }
+}
+
+impl<T: FromKscript> FromKscript for Vec<T> {
+    fn from_kscript(value: DataType) -> Result<Vec<T>, RuntimeError> {
+        let items = match value {
+            DataType::Array(ref items) => items.borrow().clone(),
+            _ => return Err(wrong_type("array", &value)),
+        };
+        items.into_iter().map(T::from_kscript).collect()
+    }
+}
+
+impl<T: IntoKscript, S> IntoKscript for HashMap<String, T, S>
+where
+    S: BuildHasher,
+{
+    fn into_kscript(self) -> DataType {
+        let dict: BTreeMap<DictKey, DataType> = self
+            .into_iter()
+            .map(|(key, value)| (DictKey::String(key), value.into_kscript()))
+            .collect();
+        DataType::Dict(Rc::new(RefCell::new(dict)))
+    }
+}
+
+// bool and integer keys are written as strings
+impl<T: FromKscript, S> FromKscript for HashMap<String, T, S>
+where
+    S: BuildHasher + Default,
+{
+    fn from_kscript(value: DataType) -> Result<HashMap<String, T, S>, RuntimeError> {
+        let dict = match value {
+            DataType::Dict(ref dict) => dict.borrow().clone(),
+            _ => return Err(wrong_type("dict", &value)),
+        };
+        dict.into_iter()
+            .map(|(key, value)| Ok((key.to_string(), T::from_kscript(value)?)))
+            .collect()
+    }
+}
+
+impl IntoKscript for UserData {
+    fn into_kscript(self) -> DataType {
+        DataType::UserData(Rc::new(self))
+    }
+}
+
+impl IntoKscript for SharedUserData {
+    fn into_kscript(self) -> DataType {
+        DataType::UserData(self)
+    }
+}
+
+// the host value is shared, downcast_ref gives back the rust value inside it
+impl FromKscript for SharedUserData {
+    fn from_kscript(value: DataType) -> Result<SharedUserData, RuntimeError> {
+        match value {
+            DataType::UserData(user_data) => Ok(user_data),
+            _ => Err(wrong_type("userdata", &value)),
+        }
+    }
+}
+
+// Some is the value, None is nothing where a script has nothing, as with an arg left out
+impl<T: FromKscript> FromKscript for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn from_kscript(value: DataType) -> Result<Option<T>, RuntimeError> {
+        Ok(Some(T::from_kscript(value)?))
+    }
+
+    fn from_arg(value: Option<DataType>) -> Result<Option<T>, RuntimeError> {
+        match value {
+            Some(value) => Self::from_kscript(value),
+            None => Ok(None),
+        }
+    }
+}
+
+// tuples are arrays with an item for each field
+macro_rules! impl_tuple {
+    ($length:expr, $($field:ident),*) => {
+        impl<$($field: IntoKscript),*> IntoKscript for ($($field,)*) {
+            #[allow(non_snake_case)]
+            fn into_kscript(self) -> DataType {
+                let ($($field,)*) = self;
+                DataType::Array(Rc::new(RefCell::new(vec![$($field.into_kscript()),*])))
+            }
+        }
+
+        impl<$($field: FromKscript),*> FromKscript for ($($field,)*) {
+            fn from_kscript(value: DataType) -> Result<($($field,)*), RuntimeError> {
+                let items = match value {
+                    DataType::Array(ref items) => items.borrow().clone(),
+                    _ => return Err(wrong_type("array", &value)),
+                };
+                if items.len() != $length {
+                    return Err(RuntimeError::ShapeMismatch($length, items.len()));
+                }
+                let mut items = items.into_iter();
+                Ok(($($field::from_arg(items.next())?,)*))
+            }
+        }
+    };
+}
+
+impl_tuple!(2, A, B);
+impl_tuple!(3, A, B, C);
+impl_tuple!(4, A, B, C, D);
+
+impl<T: IntoKscript> NativeReturn for T {
+    fn into_return(self) -> Result<Option<DataType>, RuntimeError> {
+        Ok(Some(self.into_kscript()))
+    }
+}
+
+impl NativeReturn for () {
+    fn into_return(self) -> Result<Option<DataType>, RuntimeError> {
+        Ok(None)
+    }
+}
+
+impl<T: IntoKscript> NativeReturn for Option<T> {
+    fn into_return(self) -> Result<Option<DataType>, RuntimeError> {
+        Ok(self.map(IntoKscript::into_kscript))
+    }
+}
+
+// an error is raised in the script so it can be caught
+impl<T: NativeReturn> NativeReturn for Result<T, RuntimeError> {
+    fn into_return(self) -> Result<Option<DataType>, RuntimeError> {
+        self?.into_return()
+    }
+}
+
+macro_rules! impl_into_native {
+    ($($arg:ident),*) => {
+        impl<Func, Ret, $($arg),*> IntoNative<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> Ret + 'static,
+            Ret: NativeReturn,
+            $($arg: FromKscript,)*
+        {
+            #[allow(unused_mut, unused_variables)]
+            fn into_native(self, name: &str, arg_names: &[&str]) -> NativeFunction {
+                let optional: Vec<bool> = vec![$($arg::OPTIONAL),*];
+                assert_eq!(
+                    arg_names.len(),
+                    optional.len(),
+                    "native function {} needs a name for each arg",
+                    name
+                );
+                let mut native = NativeFunction::new(name, arg_names, false, move |args: NativeArgs| {
+                    let mut values = args.into_values().into_iter();
+                    self($($arg::from_arg(values.next().and_then(|value| value))?),*).into_return()
+                });
+                native.arity.defaults = optional;
+                native
+            }
+        }
+    };
+}
+
+impl_into_native!();
+impl_into_native!(A);
+impl_into_native!(A, B);
+impl_into_native!(A, B, C);
+impl_into_native!(A, B, C, D);
+impl_into_native!(A, B, C, D, E);
@@ -1,34 +1,281 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::ops::{Add, Div, Mul, Rem, Sub};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::any::Any;
 use std::fmt;
+use super::big_int::BigInt;
 use super::command::SharedCommands;
 use super::error::RuntimeError;
 
 pub type SharedString = Rc<RefCell<String>>;
 pub type SharedArray = Rc<RefCell<Vec<DataType>>>;
+// a captured var shared by the closures and the function that made them, empty until it is set
+pub type SharedCell = Rc<RefCell<Option<DataType>>>;
+pub type SharedUpvalues = Rc<Vec<SharedCell>>;
+pub type SharedDict = Rc<RefCell<BTreeMap<DictKey, DataType>>>;
+pub type SharedArity = Rc<Arity>;
+pub type SharedNative = Rc<NativeFunction>;
+pub type SharedUserData = Rc<UserData>;
+// gets the args in the order the function takes them, like a script function it can give back nothing
+pub type NativeCallback = dyn Fn(NativeArgs) -> Result<Option<DataType>, RuntimeError>;
+// gives the text a host value is written out as
+pub type UserDisplay = dyn Fn(&UserData) -> String;
+
+// chars are stored as strings so 'a' and "a" are the same key
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DictKey {
+    Bool(bool),
+    Integer(i64),
+    String(String),
+}
+
+impl DictKey {
+    pub fn to_data_type(&self) -> DataType {
+        match *self {
+            DictKey::Bool(b) => DataType::Bool(b),
+            DictKey::Integer(int) => DataType::Integer(int),
+            DictKey::String(ref string) => DataType::String(Rc::new(RefCell::new(string.clone()))),
+        }
+    }
+}
+
+impl fmt::Display for DictKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_data_type())
+    }
+}
+
+// the args a function takes, a rest arg after them collects any extra args into an array
+#[derive(Debug, PartialEq)]
+pub struct Arity {
+    // names of the args before the rest arg
+    pub names: Vec<String>,
+    // the args the function sets itself when they are not given
+    pub defaults: Vec<bool>,
+    pub rest: bool,
+}
+
+impl Arity {
+    pub fn new() -> Arity {
+        Arity {
+            names: Vec::new(),
+            defaults: Vec::new(),
+            rest: false,
+        }
+    }
+
+    pub fn num_args(&self) -> usize {
+        self.names.len()
+    }
+}
+
+// a rust function scripts call like any other function
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: Arity,
+    pub callback: Box<NativeCallback>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, args: &[&str], rest: bool, callback: F) -> NativeFunction
+    where
+        F: Fn(NativeArgs) -> Result<Option<DataType>, RuntimeError> + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            arity: Arity {
+                names: args.iter().map(|arg| arg.to_string()).collect(),
+                defaults: vec![false; args.len()],
+                rest,
+            },
+            callback: Box::new(callback),
+        }
+    }
+}
+
+// an arg can only be left out when the native function gives it a default
+#[derive(Debug)]
+pub struct NativeArgs {
+    values: Vec<Option<DataType>>,
+}
+
+impl NativeArgs {
+    pub fn new(values: Vec<DataType>, missing: &[usize]) -> NativeArgs {
+        let mut values: Vec<Option<DataType>> = values.into_iter().map(Some).collect();
+        for index in missing.iter() {
+            values[*index] = None;
+        }
+        NativeArgs { values }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // None when the arg was left out
+    pub fn get(&self, index: usize) -> Option<&DataType> {
+        self.values.get(index).and_then(|value| value.as_ref())
+    }
+
+    // an arg that was left out or not given is an error rather than a panic
+    pub fn arg(&self, index: usize) -> Result<&DataType, RuntimeError> {
+        self.get(index).ok_or(RuntimeError::InvalidNumberOfArguments)
+    }
+
+    pub fn into_values(self) -> Vec<Option<DataType>> {
+        self.values
+    }
+
+    // the value a method is called on goes before the args
+    fn with_receiver(mut self, receiver: DataType) -> NativeArgs {
+        self.values.insert(0, Some(receiver));
+        self
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NativeFunction({}, {:?})", self.name, self.arity)
+    }
+}
+
+// a rust value scripts pass around as it is, only rust can look inside it
+pub struct UserData {
+    // the name the type builtin gives
+    pub type_name: &'static str,
+    value: Box<dyn Any>,
+    methods: HashMap<String, SharedNative>,
+    display: Option<Box<UserDisplay>>,
+}
+
+impl UserData {
+    pub fn new<T: Any>(type_name: &'static str, value: T) -> UserData {
+        UserData {
+            type_name,
+            value: Box::new(value),
+            methods: HashMap::new(),
+            display: None,
+        }
+    }
+
+    // the first arg of the method is the value it is called on, scripts call it with .[name]
+    pub fn add_method(&mut self, method: NativeFunction) {
+        assert!(
+            method.arity.num_args() > 0,
+            "method {} needs an arg for the value it is called on",
+            method.name
+        );
+        self.methods.insert(method.name.clone(), Rc::new(method));
+    }
+
+    // without one the value is written out as its type name
+    pub fn set_display<F>(&mut self, display: F)
+    where
+        F: Fn(&UserData) -> String + 'static,
+    {
+        self.display = Some(Box::new(display));
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.value.is::<T>()
+    }
+
+    // None when the value is not a T
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref::<T>()
+    }
+
+    // the method with the value given as its first arg, taking the rest of its args from the call
+    pub fn bound_method(user_data: &SharedUserData, name: &str) -> Option<NativeFunction> {
+        let method = Rc::clone(user_data.methods.get(name)?);
+        let receiver = Rc::clone(user_data);
+        let arity = Arity {
+            names: method.arity.names[1..].to_vec(),
+            defaults: method.arity.defaults[1..].to_vec(),
+            rest: method.arity.rest,
+        };
+        Some(NativeFunction {
+            name: method.name.clone(),
+            arity,
+            callback: Box::new(move |args: NativeArgs| {
+                (method.callback)(args.with_receiver(DataType::UserData(Rc::clone(&receiver))))
+            }),
+        })
+    }
+}
+
+impl fmt::Display for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.display {
+            Some(ref display) => write!(f, "{}", display(self)),
+            None => write!(f, "{}", self.type_name),
+        }
+    }
+}
+
+impl fmt::Debug for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "UserData({})", self.type_name)
+    }
+}
 
 #[derive(Debug)]
 pub enum DataType {
     Bool(bool),
     Integer(i64),
+    // only for integers that do not fit in an Integer
+    BigInt(BigInt),
     Float(f64),
     Char(char),
     String(SharedString),
     Array(SharedArray),
-    // commands ref, num args
-    Function(SharedCommands, usize),
+    Dict(SharedDict),
+    // commands ref, args taken
+    Function(SharedCommands, SharedArity),
+    // commands ref, args taken, captured values
+    Closure(SharedCommands, SharedArity, SharedUpvalues),
+    NativeFunction(SharedNative),
+    UserData(SharedUserData),
 }
 
 impl DataType {
+    // the name of the type as the type builtin gives it
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            DataType::Bool(_) => "bool",
+            DataType::Integer(_) | DataType::BigInt(_) => "int",
+            DataType::Float(_) => "float",
+            DataType::Char(_) => "char",
+            DataType::String(_) => "string",
+            DataType::Array(_) => "array",
+            DataType::Dict(_) => "dict",
+            DataType::Function(_, _) | DataType::Closure(_, _, _) | DataType::NativeFunction(_) => {
+                "function"
+            }
+            DataType::UserData(ref user_data) => user_data.type_name,
+        }
+    }
+
+    // strings are counted in chars the same as they are indexed
     pub fn len(&self) -> usize {
         match *self {
-            DataType::String(ref string) => string.borrow().len(),
+            DataType::String(ref string) => string.borrow().chars().count(),
             DataType::Array(ref array) => array.borrow().len(),
+            DataType::Dict(ref dict) => dict.borrow().len(),
             _ => 0,
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn is_bool(&self) -> bool {
         if let DataType::Bool(_) = *self {
             return true;
@@ -59,6 +306,28 @@ impl DataType {
         false
     }
 
+    // back to an Integer when the value fits
+    pub fn from_big_int(big: BigInt) -> DataType {
+        match big.to_i64() {
+            Some(int) => DataType::Integer(int),
+            None => DataType::BigInt(big),
+        }
+    }
+
+    pub fn is_big_int(&self) -> bool {
+        if let DataType::BigInt(_) = *self {
+            return true;
+        }
+        false
+    }
+
+    pub fn to_big_int(&self) -> BigInt {
+        match *self {
+            DataType::BigInt(ref big) => big.clone(),
+            _ => BigInt::from_i64(self.as_int()),
+        }
+    }
+
     pub fn as_int(&self) -> i64 {
         match *self {
             DataType::Bool(b) => if b {
@@ -87,11 +356,16 @@ impl DataType {
                 0.0
             },
             DataType::Integer(int) => int as f64,
+            DataType::BigInt(ref big) => big.to_f64(),
             DataType::Float(float) => float,
             _ => 0.0,
         }
     }
 
+    pub fn is_number(&self) -> bool {
+        self.is_int() || self.is_big_int() || self.is_float()
+    }
+
     pub fn is_char(&self) -> bool {
         if let DataType::Char(_) = *self {
             return true;
@@ -149,6 +423,30 @@ impl DataType {
         Err(RuntimeError::TargetNotAnArray)
     }
 
+    pub fn is_dict(&self) -> bool {
+        if let DataType::Dict(_) = *self {
+            return true;
+        }
+        false
+    }
+
+    pub fn get_dict(&self) -> Result<&SharedDict, RuntimeError> {
+        if let DataType::Dict(ref dict) = *self {
+            return Ok(dict);
+        }
+        Err(RuntimeError::TargetNotADict)
+    }
+
+    pub fn to_dict_key(&self) -> Result<DictKey, RuntimeError> {
+        match *self {
+            DataType::Bool(b) => Ok(DictKey::Bool(b)),
+            DataType::Integer(int) => Ok(DictKey::Integer(int)),
+            DataType::Char(c) => Ok(DictKey::String(c.to_string())),
+            DataType::String(ref string) => Ok(DictKey::String(string.borrow().clone())),
+            _ => Err(RuntimeError::InvalidDictKey),
+        }
+    }
+
     // pub fn is_fuction(&self) -> bool {
     //     if let DataType::Function(_, _) = *self {
     //         return true;
@@ -156,11 +454,134 @@ impl DataType {
     //     false
     // }
 
-    pub fn get_function(&self) -> Result<(SharedCommands, usize), RuntimeError> {
-        if let DataType::Function(ref commands, num_args) = *self {
-            return Ok((Rc::clone(commands), num_args));
+    pub fn get_function(
+        &self,
+    ) -> Result<(SharedCommands, SharedArity, Option<SharedUpvalues>), RuntimeError> {
+        match *self {
+            DataType::Function(ref commands, ref arity) => {
+                Ok((Rc::clone(commands), Rc::clone(arity), None))
+            }
+            DataType::Closure(ref commands, ref arity, ref upvalues) => Ok((
+                Rc::clone(commands),
+                Rc::clone(arity),
+                Some(Rc::clone(upvalues)),
+            )),
+            _ => Err(RuntimeError::NotAFunction),
         }
-        Err(RuntimeError::NotAFunction)
+    }
+
+    // values of different types are never equal
+    pub fn equals(&self, other: &DataType) -> Result<bool, RuntimeError> {
+        self.equals_seen(other, &mut HashSet::new())
+    }
+
+    // seen holds the pairs of arrays and dicts already being compared,
+    // meeting one again inside itself adds nothing so it is taken as equal and cycles end
+    fn equals_seen(&self, other: &DataType, seen: &mut HashSet<(usize, usize)>) -> Result<bool, RuntimeError> {
+        let equal = match (self, other) {
+            (DataType::Bool(left), DataType::Bool(right)) => left == right,
+            (DataType::Integer(left), DataType::Integer(right)) => left == right,
+            (DataType::BigInt(_), DataType::Integer(_))
+            | (DataType::Integer(_), DataType::BigInt(_))
+            | (DataType::BigInt(_), DataType::BigInt(_)) => self.to_big_int() == other.to_big_int(),
+            // exact, a float is only equal to the integer it is
+            (DataType::Integer(_), DataType::Float(float))
+            | (DataType::BigInt(_), DataType::Float(float)) => {
+                BigInt::from_f64(*float) == Some(self.to_big_int())
+            }
+            (DataType::Float(float), DataType::Integer(_))
+            | (DataType::Float(float), DataType::BigInt(_)) => {
+                BigInt::from_f64(*float) == Some(other.to_big_int())
+            }
+            (DataType::Float(left), DataType::Float(right)) => left == right,
+            (DataType::Char(left), DataType::Char(right)) => left == right,
+            (DataType::Char(_), DataType::String(_))
+            | (DataType::String(_), DataType::Char(_))
+            | (DataType::String(_), DataType::String(_)) => {
+                *self.as_string().borrow() == *other.as_string().borrow()
+            }
+            (DataType::Array(left), DataType::Array(right)) => {
+                if Rc::ptr_eq(left, right) || !seen.insert((Rc::as_ptr(left) as usize, Rc::as_ptr(right) as usize)) {
+                    return Ok(true);
+                }
+                let left = left.borrow();
+                let right = right.borrow();
+                if left.len() != right.len() {
+                    return Ok(false);
+                }
+                for (left_item, right_item) in left.iter().zip(right.iter()) {
+                    if !left_item.equals_seen(right_item, seen)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            (DataType::Dict(left), DataType::Dict(right)) => {
+                if Rc::ptr_eq(left, right) || !seen.insert((Rc::as_ptr(left) as usize, Rc::as_ptr(right) as usize)) {
+                    return Ok(true);
+                }
+                let left = left.borrow();
+                let right = right.borrow();
+                if left.len() != right.len() {
+                    return Ok(false);
+                }
+                for ((left_key, left_item), (right_key, right_item)) in left.iter().zip(right.iter()) {
+                    if left_key != right_key || !left_item.equals_seen(right_item, seen)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            // functions are only equal to themselves
+            (DataType::Function(_, _), _)
+            | (DataType::Closure(_, _, _), _)
+            | (DataType::NativeFunction(_), _) => self.is_identical(other),
+            // host values are only equal to themselves
+            (DataType::UserData(_), _) => self.is_identical(other),
+            _ => false,
+        };
+        Ok(equal)
+    }
+
+    // atoms are compared by value, everything else by the shared reference
+    pub fn is_identical(&self, other: &DataType) -> bool {
+        match (self, other) {
+            (DataType::String(left), DataType::String(right)) => Rc::ptr_eq(left, right),
+            (DataType::Array(left), DataType::Array(right)) => Rc::ptr_eq(left, right),
+            (DataType::Dict(left), DataType::Dict(right)) => Rc::ptr_eq(left, right),
+            (DataType::Function(left, _), DataType::Function(right, _)) => {
+                Rc::ptr_eq(left, right)
+            }
+            (
+                DataType::Closure(left, _, left_upvalues),
+                DataType::Closure(right, _, right_upvalues),
+            ) => Rc::ptr_eq(left, right) && Rc::ptr_eq(left_upvalues, right_upvalues),
+            (DataType::NativeFunction(left), DataType::NativeFunction(right)) => {
+                Rc::ptr_eq(left, right)
+            }
+            (DataType::UserData(left), DataType::UserData(right)) => Rc::ptr_eq(left, right),
+            (DataType::Bool(left), DataType::Bool(right)) => left == right,
+            (DataType::Integer(left), DataType::Integer(right)) => left == right,
+            (DataType::BigInt(left), DataType::BigInt(right)) => left == right,
+            (DataType::Float(left), DataType::Float(right)) => left == right,
+            (DataType::Char(left), DataType::Char(right)) => left == right,
+            _ => false,
+        }
+    }
+
+    // None when the values are unordered, as with NaN
+    pub fn compare(&self, other: &DataType) -> Result<Option<Ordering>, RuntimeError> {
+        if self.is_int() && other.is_int() {
+            return Ok(Some(self.as_int().cmp(&other.as_int())));
+        } else if self.is_number() && other.is_number() {
+            if self.is_float() || other.is_float() {
+                return Ok(self.as_float().partial_cmp(&other.as_float()));
+            }
+            return Ok(Some(self.to_big_int().cmp(&other.to_big_int())));
+        } else if self.is_char() && other.is_char() {
+            return Ok(Some(self.as_char().cmp(&other.as_char())));
+        }
+        Err(RuntimeError::CannotCompareTypes)
     }
 }
 
@@ -169,13 +590,20 @@ impl Clone for DataType {
         match *self {
             DataType::Bool(b) => DataType::Bool(b),
             DataType::Integer(int) => DataType::Integer(int),
+            DataType::BigInt(ref big) => DataType::BigInt(big.clone()),
             DataType::Float(float) => DataType::Float(float),
             DataType::Char(c) => DataType::Char(c),
             DataType::String(ref string) => DataType::String(Rc::clone(string)),
             DataType::Array(ref items) => DataType::Array(Rc::clone(items)),
-            DataType::Function(ref commands, index) => {
-                DataType::Function(Rc::clone(commands), index)
+            DataType::Dict(ref dict) => DataType::Dict(Rc::clone(dict)),
+            DataType::Function(ref commands, ref arity) => {
+                DataType::Function(Rc::clone(commands), Rc::clone(arity))
             }
+            DataType::Closure(ref commands, ref arity, ref upvalues) => {
+                DataType::Closure(Rc::clone(commands), Rc::clone(arity), Rc::clone(upvalues))
+            }
+            DataType::NativeFunction(ref native) => DataType::NativeFunction(Rc::clone(native)),
+            DataType::UserData(ref user_data) => DataType::UserData(Rc::clone(user_data)),
         }
     }
 }
@@ -185,6 +613,7 @@ impl fmt::Display for DataType {
         match *self {
             DataType::Bool(b) => write!(f, "{}", if b { "t" } else { "f" }),
             DataType::Integer(num) => write!(f, "{}", num),
+            DataType::BigInt(ref big) => write!(f, "{}", big),
             DataType::Float(float) => write!(f, "{}", float),
             DataType::Char(c) => write!(f, "{}", c),
             DataType::String(ref string) => write!(f, "{}", string.borrow()),
@@ -198,72 +627,166 @@ impl fmt::Display for DataType {
                     .collect::<Vec<String>>()
                     .join("")
             ),
+            DataType::Dict(ref dict) => write!(
+                f,
+                "{{{}}}",
+                dict.borrow()
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            DataType::UserData(ref user_data) => write!(f, "{}", user_data),
             _ => write!(f, "NYI"),
         }
     }
 }
 
+// integer results that do not fit carry on as big integers
+fn int_op<C, B>(left: &DataType, right: &DataType, checked: C, big: B) -> DataType
+where
+    C: Fn(i64, i64) -> Option<i64>,
+    B: Fn(&BigInt, &BigInt) -> BigInt,
+{
+    if !left.is_big_int() && !right.is_big_int() {
+        if let Some(int) = checked(left.as_int(), right.as_int()) {
+            return DataType::Integer(int);
+        }
+    }
+    DataType::from_big_int(big(&left.to_big_int(), &right.to_big_int()))
+}
+
 impl Add for DataType {
-    type Output = DataType;
+    type Output = Result<DataType, RuntimeError>;
 
-    fn add(self, right: DataType) -> DataType {
+    fn add(self, right: DataType) -> Result<DataType, RuntimeError> {
         if (self.is_string() || self.is_char()) && (right.is_string() || right.is_char()) {
             let left = self.as_string();
             let left = left.borrow().clone();
             let right = right.as_string();
             let right = right.borrow().clone();
-            return DataType::String(Rc::new(RefCell::new(left + &right)));
+            return Ok(DataType::String(Rc::new(RefCell::new(left + &right))));
         } else if self.is_float() || right.is_float() {
-            return DataType::Float(self.as_float() + right.as_float());
+            return Ok(DataType::Float(self.as_float() + right.as_float()));
         }
-        DataType::Integer(self.as_int() + right.as_int())
+        Ok(int_op(&self, &right, i64::checked_add, |left, right| left + right))
     }
 }
 
 impl Sub for DataType {
-    type Output = DataType;
+    type Output = Result<DataType, RuntimeError>;
 
-    fn sub(self, right: DataType) -> DataType {
+    fn sub(self, right: DataType) -> Result<DataType, RuntimeError> {
         if self.is_float() || right.is_float() {
-            return DataType::Float(self.as_float() - right.as_float());
+            return Ok(DataType::Float(self.as_float() - right.as_float()));
         }
-        DataType::Integer(self.as_int() - right.as_int())
+        Ok(int_op(&self, &right, i64::checked_sub, |left, right| left - right))
+    }
+}
+
+// a string too long to make is an overflow rather than stopping the program
+fn repeat_string(string: &str, times: usize) -> Result<String, RuntimeError> {
+    if string.is_empty() {
+        return Ok(String::new());
+    }
+    let length = match string.len().checked_mul(times) {
+        Some(length) => length,
+        None => return Err(RuntimeError::Overflow),
+    };
+    let mut repeated = String::new();
+    if repeated.try_reserve_exact(length).is_err() {
+        return Err(RuntimeError::Overflow);
     }
+    for _ in 0..times {
+        repeated.push_str(string);
+    }
+    Ok(repeated)
 }
 
 impl Mul for DataType {
-    type Output = DataType;
+    type Output = Result<DataType, RuntimeError>;
 
-    fn mul(self, right: DataType) -> DataType {
+    fn mul(self, right: DataType) -> Result<DataType, RuntimeError> {
         if self.is_string() && right.is_int() {
             let left = self.as_string();
             let left = left.borrow();
             let right = right.as_int();
-            return DataType::String(Rc::new(RefCell::new(left.repeat(right as usize))));
+            if right < 0 {
+                return Err(RuntimeError::NegativeRepeat);
+            }
+            return Ok(DataType::String(Rc::new(RefCell::new(repeat_string(&left, right as usize)?))));
         } else if self.is_float() || right.is_float() {
-            return DataType::Float(self.as_float() * right.as_float());
+            return Ok(DataType::Float(self.as_float() * right.as_float()));
         }
-        DataType::Integer(self.as_int() * right.as_int())
+        Ok(int_op(&self, &right, i64::checked_mul, |left, right| left * right))
     }
 }
 
 impl Div for DataType {
-    type Output = DataType;
+    type Output = Result<DataType, RuntimeError>;
 
-    fn div(self, right: DataType) -> DataType {
+    fn div(self, right: DataType) -> Result<DataType, RuntimeError> {
         if self.is_float() || right.is_float() {
-            return DataType::Float(self.as_float() / right.as_float());
+            return Ok(DataType::Float(self.as_float() / right.as_float()));
         }
-        DataType::Integer(self.as_int() / right.as_int())
+        if !right.is_big_int() && right.as_int() == 0 {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        Ok(int_op(&self, &right, i64::checked_div, |left, right| left.div_rem(right).0))
     }
 }
 
 impl Rem for DataType {
-    type Output = DataType;
-    fn rem(self, right: DataType) -> DataType {
+    type Output = Result<DataType, RuntimeError>;
+
+    fn rem(self, right: DataType) -> Result<DataType, RuntimeError> {
         if self.is_float() || right.is_float() {
-            return DataType::Float(self.as_float() % right.as_float());
+            return Ok(DataType::Float(self.as_float() % right.as_float()));
+        }
+        if !right.is_big_int() && right.as_int() == 0 {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        Ok(int_op(&self, &right, i64::checked_rem, |left, right| left.div_rem(right).1))
+    }
+}
+
+// the most bits an integer power can make, a bigger one is an Overflow rather than a long wait
+const MAX_POW_BITS: u64 = 1 << 18;
+
+impl DataType {
+    // an integer to an integer power is exact, a float on either side makes a float power
+    pub fn pow(&self, exponent: &DataType) -> Result<DataType, RuntimeError> {
+        let is_integer = |value: &DataType| value.is_int() || value.is_big_int();
+        if !is_integer(self) || !is_integer(exponent) {
+            return Ok(DataType::Float(self.as_float().powf(exponent.as_float())));
+        }
+        let big_exponent = exponent.to_big_int();
+        if big_exponent.is_negative() {
+            return Err(RuntimeError::NegativeExponent);
+        }
+        let base = self.to_big_int();
+        // 0, 1 and -1 stay small however big the exponent is
+        if base.is_zero() {
+            return Ok(DataType::Integer(if big_exponent.is_zero() { 1 } else { 0 }));
+        }
+        if base.bits() == 1 {
+            let negative = base.is_negative() && big_exponent.is_odd();
+            return Ok(DataType::Integer(if negative { -1 } else { 1 }));
+        }
+        let exponent = match big_exponent.to_i64() {
+            Some(exponent) if exponent <= i64::from(u32::MAX) => exponent as u32,
+            _ => return Err(RuntimeError::Overflow),
+        };
+        // the power has at least this many bits
+        match (base.bits() - 1).checked_mul(u64::from(exponent)) {
+            Some(bits) if bits <= MAX_POW_BITS => {}
+            _ => return Err(RuntimeError::Overflow),
+        }
+        if let DataType::Integer(int) = *self {
+            if let Some(int) = int.checked_pow(exponent) {
+                return Ok(DataType::Integer(int));
+            }
         }
-        DataType::Integer(self.as_int() % right.as_int())
+        Ok(DataType::from_big_int(base.pow(exponent)))
     }
 }
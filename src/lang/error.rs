@@ -1,18 +1,33 @@
 use super::builder::Ast;
+use super::data_type::DataType;
 
 #[derive(Debug)]
 pub enum RuntimeError {
     // run errors
     StackEmpty,
+    InvalidStackLength,
     CallsEmpty,
     CannotReturn,
+    // the most calls that can be running at once
+    CallLimit(usize),
     NoMoreCommands,
     InvalidNumberOfArguments,
+    // args taken, args given
+    WrongNumberOfArguments(usize, usize),
+    MissingArgument(String),
+    UnknownArgument(String),
+    DuplicateArgument(String),
+    // items a destructuring assign takes, items in the array
+    ShapeMismatch(usize, usize),
     ArgumentsNotOnStack(usize, usize),
     CannotLoadArgToStack(usize),
     CannotSaveToStackIndex(usize),
-    InvalidLocalSaveIndex(usize),
     InvalidLocalGetIndex(usize),
+    InvalidUpvalueIndex(usize),
+    InvalidGlobalGetIndex(usize),
+    LoopControlOutsideLoop,
+    // a value thrown by a script
+    Thrown(DataType),
     // type errors
     NotAFunction,
     NotABool,
@@ -20,32 +35,138 @@ pub enum RuntimeError {
     InvalidIoAppendTarget,
     TargetNotAString,
     CannotConcat,
+    CannotNegateType,
+    DivisionByZero,
+    Overflow,
+    NegativeExponent,
+    NegativeRepeat,
     TargetNotAnArray,
+    TargetNotADict,
+    InvalidDictKey,
+    KeyNotInDict,
     InvalidAccessor,
     CannotAccessWithAccessor,
     IndexOutOfBound,
     CannotInsertToString,
+    // a value a builtin cannot turn into the type asked for
+    InvalidConversion,
+    // the type rust asked for, the type of the value
+    WrongType(&'static str, &'static str),
+    // the type of the host value, the method asked for
+    UnknownMethod(&'static str, String),
+    SliceStepZero,
     CannotGetLengthOfType,
 }
 
+impl RuntimeError {
+    // the name of the error without its values
+    pub fn kind(&self) -> String {
+        let name = format!("{:?}", self);
+        match name.find('(') {
+            Some(end) => name[..end].to_string(),
+            None => name,
+        }
+    }
+
+    // what went wrong written for a person, a catch gets it as the message
+    pub fn message(&self) -> String {
+        match *self {
+            RuntimeError::StackEmpty => "the stack is empty".to_string(),
+            RuntimeError::InvalidStackLength => "the stack is shorter than expected".to_string(),
+            RuntimeError::CallsEmpty => "there is no call running".to_string(),
+            RuntimeError::CannotReturn => "cannot return from here".to_string(),
+            RuntimeError::CallLimit(limit) => format!("more than {} calls are running", limit),
+            RuntimeError::NoMoreCommands => "ran past the last command".to_string(),
+            RuntimeError::InvalidNumberOfArguments => "not enough arguments were given".to_string(),
+            RuntimeError::WrongNumberOfArguments(taken, given) => {
+                format!("takes {} arguments but {} were given", taken, given)
+            }
+            RuntimeError::MissingArgument(ref name) => format!("argument {} was not given", name),
+            RuntimeError::UnknownArgument(ref name) => format!("there is no argument named {}", name),
+            RuntimeError::DuplicateArgument(ref name) => format!("argument {} was given twice", name),
+            RuntimeError::ShapeMismatch(taken, given) => {
+                format!("takes {} items but the array has {}", taken, given)
+            }
+            RuntimeError::ArgumentsNotOnStack(found, expected) => {
+                format!("expected {} values on the stack but found {}", expected, found)
+            }
+            RuntimeError::CannotLoadArgToStack(index) => format!("argument {} is not set", index),
+            RuntimeError::CannotSaveToStackIndex(index) => {
+                format!("cannot save to stack index {}", index)
+            }
+            RuntimeError::InvalidLocalGetIndex(index) => format!("local {} is not set", index),
+            RuntimeError::InvalidUpvalueIndex(index) => {
+                format!("captured value {} is not set", index)
+            }
+            RuntimeError::InvalidGlobalGetIndex(index) => format!("global {} is not set", index),
+            RuntimeError::LoopControlOutsideLoop => "break or continue outside a loop".to_string(),
+            RuntimeError::Thrown(ref value) => value.to_string(),
+            RuntimeError::NotAFunction => "the value called is not a function".to_string(),
+            RuntimeError::NotABool => "expected a bool".to_string(),
+            RuntimeError::CannotCompareTypes => "the values cannot be compared".to_string(),
+            RuntimeError::InvalidIoAppendTarget => "can only write to 1 or 2".to_string(),
+            RuntimeError::TargetNotAString => "expected a string".to_string(),
+            RuntimeError::CannotConcat => "the values cannot be joined".to_string(),
+            RuntimeError::CannotNegateType => "the value cannot be negated".to_string(),
+            RuntimeError::DivisionByZero => "division by zero".to_string(),
+            RuntimeError::Overflow => "the result does not fit in an integer".to_string(),
+            RuntimeError::NegativeExponent => "an integer cannot be raised to a negative power".to_string(),
+            RuntimeError::NegativeRepeat => "cannot repeat a negative number of times".to_string(),
+            RuntimeError::TargetNotAnArray => "expected an array".to_string(),
+            RuntimeError::TargetNotADict => "expected a dict".to_string(),
+            RuntimeError::InvalidDictKey => "a dict key must be a bool, int or string".to_string(),
+            RuntimeError::KeyNotInDict => "the key is not in the dict".to_string(),
+            RuntimeError::InvalidAccessor => "the index is not an int".to_string(),
+            RuntimeError::CannotAccessWithAccessor => "the value cannot be indexed with that".to_string(),
+            RuntimeError::IndexOutOfBound => "the index is out of bounds".to_string(),
+            RuntimeError::CannotInsertToString => "cannot insert into a string".to_string(),
+            RuntimeError::InvalidConversion => "the value cannot be converted".to_string(),
+            RuntimeError::WrongType(expected, found) => format!("expected {} but got {}", expected, found),
+            RuntimeError::UnknownMethod(type_name, ref method) => {
+                format!("{} has no method {}", type_name, method)
+            }
+            RuntimeError::SliceStepZero => "the step of a slice cannot be zero".to_string(),
+            RuntimeError::CannotGetLengthOfType => "the value has no length".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ParserError {
     FileLoadFile(String),
+    ModuleNotFound(String),
+    ImportCycle(String),
+    InvalidImport,
+    InvalidGlobal,
     InvalidComment,
     InvalidVar,
     InvalidAssign,
     InvalidEquals,
+    InvalidNotEquals,
+    InvalidLessThan,
+    InvalidGreaterThan,
     InvalidNumber,
     InvalidFloat,
     InvalidIoWrite,
     InvalidIoAppend,
     InvalidBlockStart,
     InvalidBlock,
+    InvalidElse,
+    IfWithoutElseAsValue,
+    InvalidEach,
+    InvalidWhile,
+    LoopControlOutsideLoop,
+    InvalidTry,
     InvalidItem,
     InvalidTotalArgs(usize, usize),
+    InvalidRest,
+    InvalidNamedArg,
+    InvalidPattern,
+    UnknownArgument(String),
     CannotConvetAstToDataType(Ast),
     CannotConvertAstToCommand(Ast),
     CannotSaveFromAst(Ast),
+    MissingOperand(Ast),
     InvalidPart,
     InvalidStringStart,
     InvalidString,
@@ -57,8 +178,10 @@ pub enum ParserError {
     InvalidDiv,
     InvalidRem,
     InvalidArrayItem,
+    InvalidDict,
     InvalidFunction,
     InvalidAccessCall,
+    InvalidSlice,
 }
 
 #[derive(Debug)]
@@ -68,4 +191,8 @@ pub enum KscriptError {
     VmCommandsEmpty,
     CannotParseArgs(String),
     CallDataEmpty,
+    // no global of the name to call
+    UnknownGlobal(String),
+    // the function called from rust gave back nothing
+    NoReturnValue,
 }
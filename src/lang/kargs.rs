@@ -28,6 +28,7 @@ impl ArgContainer {
 pub enum ArgFlags {
     Debug,
     DebugFile(String),
+    ModulePath(String),
     Help,
 }
 
@@ -51,6 +52,8 @@ pub fn parse_args() -> Result<Kargs, String> {
             "-d" | "--debug" => flags.push(ArgFlags::Debug),
             "-df" | "--debug-file" => flags
                 .push(ArgFlags::DebugFile(arg_container.next_arg("Cannot get the debug file")?)),
+            "-m" | "--module-path" => flags
+                .push(ArgFlags::ModulePath(arg_container.next_arg("Cannot get the module path")?)),
             _ => file = Some(next_arg),
         };
     }
@@ -67,5 +70,6 @@ pub fn help_message(zero: &str) {
     println!("-h | --help : Show this help message");
     println!("-d | --debug : Print debug info to stdout");
     println!("-df <file> | --debug-file <file> : Write debug info to specified file");
+    println!("-m <dir> | --module-path <dir> : Also look for imported files in the directory");
     process::exit(1);
 }
@@ -6,14 +6,21 @@ mod builder;
 mod error;
 mod kargs;
 mod access;
+mod big_int;
+mod prelude;
+mod convert;
 
 use std::io::{self, Write};
+use std::rc::Rc;
 use self::vm::{CallInfo, Vm};
 use self::util::{load_file_to_string, write_debug, KscriptDebug};
 use self::command::SharedCommands;
 use self::builder::{build_commands, SymbolTable};
-use self::error::{KscriptError, ParserError};
 use self::kargs::{help_message, parse_args, ArgFlags};
+use self::prelude::prelude;
+pub use self::data_type::{DataType, NativeArgs, NativeFunction, SharedUserData, UserData};
+pub use self::convert::{FromKscript, IntoKscript, IntoNative, NativeReturn};
+pub use self::error::{KscriptError, ParserError, RuntimeError};
 
 const REPL_INTRO: &str = "Kscript REPL, CTRL-D to exit";
 
@@ -28,13 +35,74 @@ pub struct Kscript {
 
 impl Kscript {
     pub fn new() -> Kscript {
-        Kscript {
+        let mut kscript = Kscript {
             symbols: SymbolTable::new(),
             commands: None,
             vm: Vm::new(),
             vm_calls: None,
             debug: None,
+        };
+        for native in prelude() {
+            kscript.add_native(native);
         }
+        kscript
+    }
+
+    // a rust function scripts can call by name, a variable of the same name in a script hides it
+    pub fn add_native(&mut self, native: NativeFunction) {
+        let index = self.symbols.add_builtin(&native.name);
+        self.set_global_slot(index, DataType::NativeFunction(Rc::new(native)));
+    }
+
+    // a top level variable of the scripts run so far or a builtin
+    pub fn get_global(&self, name: &str) -> Option<DataType> {
+        let index = self.symbols.global(name)?;
+        let root_calls = self.vm_calls.as_ref()?.first()?;
+        root_calls.locals.get(index).cloned()?
+    }
+
+    // scripts run after this see the value as a top level variable
+    pub fn set_global(&mut self, name: &str, value: DataType) {
+        let index = self.symbols.global_target(name);
+        self.set_global_slot(index, value);
+    }
+
+    // call a function held in a global, the globals keep any changes it makes
+    pub fn call(&mut self, name: &str, args: Vec<DataType>) -> Result<DataType, KscriptError> {
+        let function = match self.get_global(name) {
+            Some(function) => function,
+            None => return Err(KscriptError::UnknownGlobal(name.to_string())),
+        };
+        let vm_calls = match self.vm_calls {
+            Some(ref mut vm_calls) => vm_calls,
+            None => return Err(KscriptError::CallDataEmpty),
+        };
+        match self.vm.call_function(vm_calls, &function, args) {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(KscriptError::NoReturnValue),
+            Err(error) => Err(KscriptError::RuntimeError(error)),
+        }
+    }
+
+    fn set_global_slot(&mut self, index: usize, value: DataType) {
+        let globals = &mut self.root_calls().locals;
+        if index >= globals.len() {
+            globals.resize(index + 1, None);
+        }
+        globals[index] = Some(value);
+    }
+
+    // the first call holds the globals, it is made before anything is run so they can be set
+    fn root_calls(&mut self) -> &mut CallInfo {
+        let calls = self
+            .vm_calls
+            .get_or_insert_with(|| Vm::create_calls(&Rc::new(Vec::new())));
+        &mut calls[0]
+    }
+
+    // the most function calls that can be running at once, a call past it is a CallLimit error
+    pub fn set_call_limit(&mut self, limit: usize) {
+        self.vm.set_call_limit(Some(limit));
     }
 
     pub fn set_debug(&mut self) {
@@ -45,6 +113,10 @@ impl Kscript {
         self.debug = Some(KscriptDebug::File(filename.to_string()));
     }
 
+    pub fn add_module_path(&mut self, path: &str) {
+        self.symbols.modules.add_search_path(path);
+    }
+
     pub fn run_from_args(&mut self) -> Result<i32, KscriptError> {
         let kargs = match parse_args() {
             Ok(kargs) => kargs,
@@ -56,6 +128,7 @@ impl Kscript {
                 ArgFlags::Help => help_message(&kargs.zero),
                 ArgFlags::Debug => self.set_debug(),
                 ArgFlags::DebugFile(ref filename) => self.set_debug_file(filename),
+                ArgFlags::ModulePath(ref path) => self.add_module_path(path),
             };
         }
 
@@ -89,11 +162,8 @@ impl Kscript {
                 Ok(exit_code) => exit_code,
                 Err(error) => {
                     println!("{:?}", error);
-                    // reset the symbol table counter
+                    // the global slots given out stay taken, names defined later must not reuse them
                     if let Some(ref mut calls) = self.vm_calls {
-                        if let Some(root_cals) = calls.first() {
-                            self.symbols.set_counter(root_cals.locals.len());
-                        }
                         while calls.len() > 1 {
                             // empty function stack
                             calls.pop();
@@ -129,7 +199,10 @@ impl Kscript {
             write_debug("File String", &program, &self.debug).unwrap();
         }
 
-        self.run_string(&program)
+        self.symbols.modules.start_file(filename);
+        let exit_code = self.run_string(&program);
+        self.symbols.modules.end_file();
+        exit_code
     }
 
     pub fn run_string(&mut self, program: &str) -> Result<i32, KscriptError> {
@@ -0,0 +1,97 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use super::big_int::BigInt;
+use super::data_type::{DataType, NativeArgs, NativeFunction};
+use super::error::RuntimeError;
+
+// the builtins every script starts with
+pub fn prelude() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction::new("len", &["value"], false, len),
+        NativeFunction::new("type", &["value"], false, type_name),
+        NativeFunction::new("str", &["value"], false, to_str),
+        NativeFunction::new("int", &["value"], false, to_int),
+        NativeFunction::new("float", &["value"], false, to_float),
+        NativeFunction::new("print", &[], true, print),
+    ]
+}
+
+fn new_string(string: String) -> DataType {
+    DataType::String(Rc::new(RefCell::new(string)))
+}
+
+// the same as @?
+fn len(args: NativeArgs) -> Result<Option<DataType>, RuntimeError> {
+    let value = args.arg(0)?;
+    if !(value.is_string() || value.is_array() || value.is_dict()) {
+        return Err(RuntimeError::CannotGetLengthOfType);
+    }
+    Ok(Some(DataType::Integer(value.len() as i64)))
+}
+
+fn type_name(args: NativeArgs) -> Result<Option<DataType>, RuntimeError> {
+    Ok(Some(new_string(args.arg(0)?.type_name().to_string())))
+}
+
+fn to_str(args: NativeArgs) -> Result<Option<DataType>, RuntimeError> {
+    Ok(Some(new_string(args.arg(0)?.to_string())))
+}
+
+// floats are cut towards zero, strings are read as a whole number with an optional -
+fn to_int(args: NativeArgs) -> Result<Option<DataType>, RuntimeError> {
+    let value = args.arg(0)?;
+    let int = match *value {
+        DataType::Integer(_) | DataType::BigInt(_) => value.clone(),
+        DataType::Bool(b) => DataType::Integer(b as i64),
+        DataType::Float(float) => {
+            if !float.is_finite() {
+                return Err(RuntimeError::InvalidConversion);
+            }
+            parse_int(&format!("{:.0}", float.trunc()))?
+        }
+        DataType::Char(_) | DataType::String(_) => parse_int(value.as_string().borrow().trim())?,
+        _ => return Err(RuntimeError::InvalidConversion),
+    };
+    Ok(Some(int))
+}
+
+fn parse_int(digits: &str) -> Result<DataType, RuntimeError> {
+    if let Ok(int) = digits.parse::<i64>() {
+        return Ok(DataType::Integer(int));
+    }
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, digits),
+    };
+    match BigInt::parse(digits) {
+        Some(big) if negative => Ok(DataType::from_big_int(-&big)),
+        Some(big) => Ok(DataType::from_big_int(big)),
+        None => Err(RuntimeError::InvalidConversion),
+    }
+}
+
+fn to_float(args: NativeArgs) -> Result<Option<DataType>, RuntimeError> {
+    let value = args.arg(0)?;
+    let float = match *value {
+        DataType::Integer(_) | DataType::BigInt(_) | DataType::Float(_) => value.as_float(),
+        DataType::Char(_) | DataType::String(_) => {
+            match value.as_string().borrow().trim().parse::<f64>() {
+                Ok(float) => float,
+                Err(_) => return Err(RuntimeError::InvalidConversion),
+            }
+        }
+        _ => return Err(RuntimeError::InvalidConversion),
+    };
+    Ok(Some(DataType::Float(float)))
+}
+
+// writes each arg to STDOUT split by spaces with a new line
+fn print(args: NativeArgs) -> Result<Option<DataType>, RuntimeError> {
+    let items = match *args.arg(0)? {
+        DataType::Array(ref items) => items.borrow(),
+        _ => return Err(RuntimeError::TargetNotAnArray),
+    };
+    let line: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+    println!("{}", line.join(" "));
+    Ok(None)
+}
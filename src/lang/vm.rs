@@ -1,46 +1,133 @@
 use std::rc::Rc;
 use std::cell::RefCell;
-use super::command::{Command, SharedCommands};
-use super::data_type::DataType;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use super::big_int::BigInt;
+use super::command::{Capture, Command, SharedCommands};
+use super::data_type::{Arity, DataType, DictKey, NativeArgs, NativeFunction, SharedArity,
+                       SharedCell, SharedDict, SharedUpvalues};
 use super::error::RuntimeError;
-use super::access::{get_item_in_collection, update_elememnt_in_collection};
+use super::access::{get_item_in_collection, get_iterable, has_key_in_collection, match_length,
+                    remove_key_from_collection, rest_from, get_slice, update_slice,
+                    update_elememnt_in_collection, insert_in_collection, push_to_collection,
+                    pop_from_collection, remove_slice, SliceBounds};
 
 #[derive(Debug)]
 pub struct CallInfo {
     pub commands: SharedCommands,
+    pub arity: SharedArity,
+    // stack slots taken by the args, including the rest array
     pub num_args: usize,
+    // args left out of the call that are set to their defaults
+    pub missing: Vec<usize>,
     pub stack_index: usize,
     pub command_index: usize,
-    pub locals: Vec<DataType>,
+    // locals can be skipped when they are set in a branch that is not run
+    pub locals: Vec<Option<DataType>>,
+    // locals and args captured by a closure, once captured they are read and set through the cell
+    pub local_cells: Vec<Option<SharedCell>>,
+    pub arg_cells: Vec<Option<SharedCell>>,
+    pub upvalues: Option<SharedUpvalues>,
+    // try blocks this call is in, the last is the closest
+    pub handlers: Vec<Handler>,
+}
+
+#[derive(Debug)]
+pub struct Handler {
+    pub stack_len: usize,
+    pub command_index: usize,
 }
 
 impl CallInfo {
+    pub fn new(commands: SharedCommands, arity: SharedArity, stack_index: usize) -> CallInfo {
+        CallInfo {
+            commands,
+            arity,
+            num_args: 0,
+            missing: Vec::new(),
+            stack_index,
+            command_index: 0,
+            locals: Vec::new(),
+            local_cells: Vec::new(),
+            arg_cells: Vec::new(),
+            upvalues: None,
+            handlers: Vec::new(),
+        }
+    }
+
     pub fn update_commands(&mut self, commands: &SharedCommands) {
         self.commands = Rc::clone(commands);
         self.command_index = 0;
     }
+
+    fn local_cell(&self, index: usize) -> Option<&SharedCell> {
+        self.local_cells.get(index).and_then(|cell| cell.as_ref())
+    }
+
+    fn arg_cell(&self, index: usize) -> Option<&SharedCell> {
+        self.arg_cells.get(index).and_then(|cell| cell.as_ref())
+    }
+
+    // a local that is not set yet is captured empty so a closure can refer to itself
+    fn capture_local(&mut self, index: usize) -> SharedCell {
+        if let Some(cell) = self.local_cell(index) {
+            return Rc::clone(cell);
+        }
+        let value = self.locals.get(index).cloned().unwrap_or(None);
+        add_cell(&mut self.local_cells, index, value)
+    }
+
+    fn capture_arg(&mut self, index: usize, value: Option<DataType>) -> SharedCell {
+        if let Some(cell) = self.arg_cell(index) {
+            return Rc::clone(cell);
+        }
+        add_cell(&mut self.arg_cells, index, value)
+    }
+}
+
+fn add_cell(cells: &mut Vec<Option<SharedCell>>, index: usize, value: Option<DataType>) -> SharedCell {
+    if index >= cells.len() {
+        cells.resize(index + 1, None);
+    }
+    let cell = Rc::new(RefCell::new(value));
+    cells[index] = Some(Rc::clone(&cell));
+    cell
 }
 
 #[derive(Debug)]
 pub struct Vm {
     stack: Vec<DataType>,
+    call_limit: Option<usize>,
+    // the namespace and key each top level name of a module is kept up to date in
+    exports: Vec<Option<(SharedDict, DictKey)>>,
 }
 
 impl Vm {
     pub fn new() -> Vm {
-        Vm { stack: Vec::new() }
+        Vm {
+            stack: Vec::new(),
+            call_limit: None,
+            exports: Vec::new(),
+        }
+    }
+
+    pub fn set_call_limit(&mut self, limit: Option<usize>) {
+        self.call_limit = limit;
+    }
+
+    // the root call holds the globals so it is not counted
+    fn push_call(&self, calls: &mut Vec<CallInfo>, new_calls: CallInfo) -> Result<(), RuntimeError> {
+        if let Some(limit) = self.call_limit {
+            if calls.len() > limit {
+                return Err(RuntimeError::CallLimit(limit));
+            }
+        }
+        calls.push(new_calls);
+        Ok(())
     }
 
     pub fn create_calls(commands: &SharedCommands) -> Vec<CallInfo> {
-        vec![
-            CallInfo {
-                commands: Rc::clone(commands),
-                num_args: 0,
-                stack_index: 0,
-                command_index: 0,
-                locals: Vec::new(),
-            },
-        ]
+        vec![CallInfo::new(Rc::clone(commands), Rc::new(Arity::new()), 0)]
     }
 
     pub fn get_stack_mut(&mut self) -> &mut Vec<DataType> {
@@ -48,11 +135,29 @@ impl Vm {
     }
 
     pub fn run(&mut self, calls: &mut Vec<CallInfo>) -> Result<i32, RuntimeError> {
+        self.run_calls(calls, 0)
+    }
+
+    // the calls under the depth belong to whoever started the run, it stops when they are all that is left
+    fn run_calls(&mut self, calls: &mut Vec<CallInfo>, depth: usize) -> Result<i32, RuntimeError> {
         loop {
-            let (mabe_new_calls, do_return, mabe_exit_code) = match calls.last_mut() {
-                Some(ref mut current_calls) => self.match_command(current_calls)?,
+            // the first call holds the globals as its locals
+            let result = match calls.split_first_mut() {
+                Some((root_calls, rest)) => match rest.last_mut() {
+                    Some(current_calls) => {
+                        self.match_command(current_calls, Some(&mut root_calls.locals))
+                    }
+                    None => self.match_command(root_calls, None),
+                },
                 None => return Err(RuntimeError::CallsEmpty),
             };
+            let (mabe_new_calls, do_return, mabe_exit_code) = match result {
+                Ok(result) => result,
+                Err(error) => {
+                    self.catch_error(calls, error, depth)?;
+                    continue;
+                }
+            };
             if let Some(code) = mabe_exit_code {
                 return Ok(code);
             }
@@ -61,14 +166,48 @@ impl Vm {
                 if let None = rst {
                     return Err(RuntimeError::CannotReturn);
                 }
+                if depth > 0 && calls.len() == depth {
+                    return Ok(0);
+                }
                 continue;
             }
             if let Some(new_calls) = mabe_new_calls {
-                calls.push(new_calls);
+                if let Err(error) = self.push_call(calls, new_calls) {
+                    self.catch_error(calls, error, depth)?;
+                }
             }
         }
     }
 
+    // unwind to the closest try block, the error is given to it as a value,
+    // calls under the depth are never unwound
+    fn catch_error(
+        &mut self,
+        calls: &mut Vec<CallInfo>,
+        error: RuntimeError,
+        depth: usize,
+    ) -> Result<(), RuntimeError> {
+        while calls.len() > depth {
+            let mut current_calls = match calls.pop() {
+                Some(current_calls) => current_calls,
+                None => break,
+            };
+            if let Some(handler) = current_calls.handlers.pop() {
+                self.stack.truncate(handler.stack_len);
+                self.stack.push(error_to_value(error));
+                current_calls.command_index = handler.command_index;
+                calls.push(current_calls);
+                return Ok(());
+            }
+            if calls.is_empty() {
+                // keep the root call so the globals survive the error
+                calls.push(current_calls);
+                break;
+            }
+        }
+        Err(error)
+    }
+
     fn pop_stack(&mut self) -> Result<DataType, RuntimeError> {
         if let Some(data) = self.stack.pop() {
             return Ok(data);
@@ -76,9 +215,224 @@ impl Vm {
         Err(RuntimeError::StackEmpty)
     }
 
+    // the parts of a slice are pushed in order so they come off from the step back
+    fn pop_slice_bounds(&mut self, start: bool, end: bool, step: bool) -> Result<SliceBounds, RuntimeError> {
+        let mut bounds = [None; 3];
+        for (index, given) in [start, end, step].iter().enumerate().rev() {
+            if !given {
+                continue;
+            }
+            let part = self.pop_stack()?;
+            if !part.is_int() {
+                return Err(RuntimeError::InvalidAccessor);
+            }
+            bounds[index] = Some(part.as_int());
+        }
+        Ok((bounds[0], bounds[1], bounds[2]))
+    }
+
+    fn compare(&mut self) -> Result<Option<Ordering>, RuntimeError> {
+        let right = self.pop_stack()?;
+        let left = self.pop_stack()?;
+        left.compare(&right)
+    }
+
+    // put the args given in the order the function takes them, extra args are moved into the rest array,
+    // gives the stack slots taken and the args left out for their defaults to be used
+    fn bind_args(
+        &mut self,
+        arity: &Arity,
+        given: usize,
+        names: &[String],
+    ) -> Result<(usize, Vec<usize>), RuntimeError> {
+        if self.stack.len() < given || given < names.len() {
+            return Err(RuntimeError::InvalidNumberOfArguments);
+        }
+        let num_args = arity.num_args();
+        let positional = given - names.len();
+        if names.is_empty() && positional == num_args && !arity.rest {
+            return Ok((given, Vec::new()));
+        }
+        if positional > num_args && !arity.rest {
+            return Err(RuntimeError::WrongNumberOfArguments(num_args, given));
+        }
+
+        let named_start = self.stack.len() - names.len();
+        let named = self.stack.split_off(named_start);
+        let rest_start = self.stack.len() - positional.saturating_sub(num_args);
+        let rest = self.stack.split_off(rest_start);
+
+        let first_unset = positional.min(num_args);
+        let mut unset: Vec<Option<DataType>> = vec![None; num_args - first_unset];
+        for (name, value) in names.iter().zip(named) {
+            let index = match arity.names.iter().position(|arg_name| arg_name == name) {
+                Some(index) => index,
+                None => return Err(RuntimeError::UnknownArgument(name.clone())),
+            };
+            if index < first_unset || unset[index - first_unset].is_some() {
+                return Err(RuntimeError::DuplicateArgument(name.clone()));
+            }
+            unset[index - first_unset] = Some(value);
+        }
+
+        let mut missing = Vec::new();
+        for (offset, value) in unset.into_iter().enumerate() {
+            let index = first_unset + offset;
+            match value {
+                Some(value) => self.stack.push(value),
+                None if arity.defaults[index] => {
+                    missing.push(index);
+                    // a place for the default to be saved to
+                    self.stack.push(DataType::Bool(false));
+                }
+                None if names.is_empty() => {
+                    return Err(RuntimeError::WrongNumberOfArguments(num_args, given))
+                }
+                None => return Err(RuntimeError::MissingArgument(arity.names[index].clone())),
+            }
+        }
+        if arity.rest {
+            self.stack.push(DataType::Array(Rc::new(RefCell::new(rest))));
+            return Ok((num_args + 1, missing));
+        }
+        Ok((num_args, missing))
+    }
+
+    // call a function from rust, any value it gives back is taken off the stack
+    pub fn call_function(
+        &mut self,
+        calls: &mut Vec<CallInfo>,
+        function: &DataType,
+        args: Vec<DataType>,
+    ) -> Result<Option<DataType>, RuntimeError> {
+        let depth = calls.len();
+        let stack_len = self.stack.len();
+        let given = args.len();
+        self.stack.extend(args);
+        let result = self.run_function(calls, function, given, depth);
+        let mut value = None;
+        if result.is_ok() && self.stack.len() > stack_len {
+            value = self.stack.pop();
+        }
+        // nothing is left behind when the function fails
+        self.stack.truncate(stack_len);
+        calls.truncate(depth);
+        result.map(|_| value)
+    }
+
+    fn run_function(
+        &mut self,
+        calls: &mut Vec<CallInfo>,
+        function: &DataType,
+        given: usize,
+        depth: usize,
+    ) -> Result<(), RuntimeError> {
+        if let DataType::NativeFunction(ref native) = *function {
+            return self.run_native(native, given, &[]);
+        }
+        let (body, arity, upvalues) = function.get_function()?;
+        let new_calls = self.new_call(body, arity, upvalues, given, &[])?;
+        self.push_call(calls, new_calls)?;
+        self.run_calls(calls, depth)?;
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        current_calls: &mut CallInfo,
+        body: SharedCommands,
+        arity: SharedArity,
+        upvalues: Option<SharedUpvalues>,
+        given: usize,
+        names: &[String],
+    ) -> Result<(Option<CallInfo>, bool, Option<i32>), RuntimeError> {
+        current_calls.command_index += 1;
+        let new_calls = self.new_call(body, arity, upvalues, given, names)?;
+        Ok((Some(new_calls), false, None))
+    }
+
+    fn new_call(
+        &mut self,
+        body: SharedCommands,
+        arity: SharedArity,
+        upvalues: Option<SharedUpvalues>,
+        given: usize,
+        names: &[String],
+    ) -> Result<CallInfo, RuntimeError> {
+        let (num_args, missing) = self.bind_args(&arity, given, names)?;
+        let mut new_calls = CallInfo::new(body, arity, self.stack.len() - num_args);
+        new_calls.num_args = num_args;
+        new_calls.missing = missing;
+        new_calls.upvalues = upvalues;
+        Ok(new_calls)
+    }
+
+    fn call_native(
+        &mut self,
+        current_calls: &mut CallInfo,
+        native: &NativeFunction,
+        given: usize,
+        names: &[String],
+    ) -> Result<(Option<CallInfo>, bool, Option<i32>), RuntimeError> {
+        self.run_native(native, given, names)?;
+        current_calls.command_index += 1;
+        Ok((None, false, None))
+    }
+
+    // the args are taken off the stack and the value given back is left in their place
+    fn run_native(
+        &mut self,
+        native: &NativeFunction,
+        given: usize,
+        names: &[String],
+    ) -> Result<(), RuntimeError> {
+        let (num_args, missing) = self.bind_args(&native.arity, given, names)?;
+        let args_start = self.stack.len() - num_args;
+        let args = NativeArgs::new(self.stack.split_off(args_start), &missing);
+        if let Some(value) = (native.callback)(args)? {
+            self.stack.push(value);
+        }
+        Ok(())
+    }
+
+    // the args of the current call are replaced by the new args so deep recursion uses no more memory,
+    // a normal call is made when values are left on the stack or a try block is open
+    fn tail_call(
+        &mut self,
+        current_calls: &mut CallInfo,
+        body: SharedCommands,
+        arity: SharedArity,
+        upvalues: Option<SharedUpvalues>,
+        given: usize,
+        names: &[String],
+    ) -> Result<(Option<CallInfo>, bool, Option<i32>), RuntimeError> {
+        if self.stack.len() < given {
+            return Err(RuntimeError::InvalidNumberOfArguments);
+        }
+        let current_args_end = current_calls.stack_index + current_calls.num_args;
+        if self.stack.len() - given != current_args_end || !current_calls.handlers.is_empty() {
+            return self.call(current_calls, body, arity, upvalues, given, names);
+        }
+
+        let (num_args, missing) = self.bind_args(&arity, given, names)?;
+        self.stack.drain(current_calls.stack_index..current_args_end);
+        current_calls.commands = body;
+        current_calls.arity = arity;
+        current_calls.num_args = num_args;
+        current_calls.missing = missing;
+        current_calls.command_index = 0;
+        current_calls.locals.clear();
+        current_calls.local_cells.clear();
+        current_calls.arg_cells.clear();
+        current_calls.upvalues = upvalues;
+
+        Ok((None, false, None))
+    }
+
     fn match_command(
         &mut self,
         current_calls: &mut CallInfo,
+        globals: Option<&mut Vec<Option<DataType>>>,
     ) -> Result<(Option<CallInfo>, bool, Option<i32>), RuntimeError> {
         let command = match current_calls.commands.get(current_calls.command_index) {
             Some(ref_cmd) => ref_cmd.clone(),
@@ -88,21 +442,96 @@ impl Vm {
             Command::PushStack(data) => self.stack.push(data),
             Command::SaveLocal(index) => {
                 let value = self.pop_stack()?;
-                if index < current_calls.locals.len() {
-                    current_calls.locals[index] = value;
-                } else if index == current_calls.locals.len() {
-                    current_calls.locals.push(value);
+                if let Some(cell) = current_calls.local_cell(index) {
+                    *cell.borrow_mut() = Some(value);
                 } else {
-                    return Err(RuntimeError::InvalidLocalSaveIndex(index));
+                    if index >= current_calls.locals.len() {
+                        current_calls.locals.resize(index + 1, None);
+                    }
+                    current_calls.locals[index] = Some(value);
+                }
+            }
+            Command::UnshareLocal(index) => {
+                if let Some(cell) = current_calls.local_cells.get_mut(index) {
+                    *cell = None;
                 }
             }
             Command::LoadLocal(index) => {
-                let value = match current_calls.locals.get(index) {
-                    Some(value) => value.clone(),
+                let value = match current_calls.local_cell(index) {
+                    Some(cell) => cell.borrow().clone(),
+                    None => current_calls.locals.get(index).cloned().unwrap_or(None),
+                };
+                match value {
+                    Some(value) => self.stack.push(value),
                     None => return Err(RuntimeError::InvalidLocalGetIndex(index)),
+                }
+            }
+            Command::SaveGlobal(index) => {
+                let value = self.pop_stack()?;
+                if let Some(&Some((ref namespace, ref key))) = self.exports.get(index) {
+                    namespace.borrow_mut().insert(key.clone(), value.clone());
+                }
+                let globals = globals.unwrap_or(&mut current_calls.locals);
+                if index >= globals.len() {
+                    globals.resize(index + 1, None);
+                }
+                globals[index] = Some(value);
+            }
+            Command::LoadGlobal(index) => {
+                let globals = globals.unwrap_or(&mut current_calls.locals);
+                let value = match globals.get(index) {
+                    Some(Some(value)) => value.clone(),
+                    _ => return Err(RuntimeError::InvalidGlobalGetIndex(index)),
                 };
                 self.stack.push(value);
             }
+            Command::Export(ref names) => {
+                let namespace = Rc::new(RefCell::new(BTreeMap::new()));
+                for &(ref name, index) in names.iter() {
+                    if index >= self.exports.len() {
+                        self.exports.resize(index + 1, None);
+                    }
+                    self.exports[index] = Some((Rc::clone(&namespace), DictKey::String(name.clone())));
+                }
+                self.stack.push(DataType::Dict(namespace));
+            }
+            Command::LoadUpvalue(index) => {
+                let value = match upvalue(current_calls, index) {
+                    Some(cell) => cell.borrow().clone(),
+                    None => None,
+                };
+                match value {
+                    Some(value) => self.stack.push(value),
+                    None => return Err(RuntimeError::InvalidUpvalueIndex(index)),
+                };
+            }
+            Command::SaveUpvalue(index) => {
+                let value = self.pop_stack()?;
+                match upvalue(current_calls, index) {
+                    Some(cell) => *cell.borrow_mut() = Some(value),
+                    None => return Err(RuntimeError::InvalidUpvalueIndex(index)),
+                };
+            }
+            Command::InitClosure(ref captures) => {
+                let mut upvalues = Vec::new();
+                for capture in captures.iter() {
+                    let cell = match *capture {
+                        Capture::Local(index) => current_calls.capture_local(index),
+                        Capture::Arg(index) => {
+                            let value = self.stack.get(current_calls.stack_index + index).cloned();
+                            current_calls.capture_arg(index, value)
+                        }
+                        Capture::Upvalue(index) => match upvalue(current_calls, index) {
+                            Some(cell) => cell,
+                            None => return Err(RuntimeError::InvalidUpvalueIndex(index)),
+                        },
+                    };
+                    upvalues.push(cell);
+                }
+                let function = self.pop_stack()?;
+                let (body, arity, _) = function.get_function()?;
+                self.stack.push(DataType::Closure(body, arity, Rc::new(upvalues)));
+            }
             Command::InitArray => {
                 self.stack
                     .push(DataType::Array(Rc::new(RefCell::new(Vec::new()))));
@@ -116,9 +545,80 @@ impl Vm {
                 }
                 self.stack.push(target);
             }
+            Command::InitString => {
+                self.stack
+                    .push(DataType::String(Rc::new(RefCell::new(String::new()))));
+            }
+            Command::StringPush => {
+                let value = self.pop_stack()?;
+                let target = self.pop_stack()?;
+                {
+                    let shared_string = target.get_string()?;
+                    shared_string.borrow_mut().push_str(&value.to_string());
+                }
+                self.stack.push(target);
+            }
+            Command::InitDict => {
+                self.stack
+                    .push(DataType::Dict(Rc::new(RefCell::new(BTreeMap::new()))));
+            }
+            Command::DictInsert => {
+                let value = self.pop_stack()?;
+                let key = self.pop_stack()?;
+                let target = self.pop_stack()?;
+                {
+                    let shared_dict = target.get_dict()?;
+                    shared_dict.borrow_mut().insert(key.to_dict_key()?, value);
+                }
+                self.stack.push(target);
+            }
+            Command::Has => {
+                let accessor = self.pop_stack()?;
+                let target = self.pop_stack()?;
+                self.stack
+                    .push(DataType::Bool(has_key_in_collection(accessor, target)?));
+            }
+            Command::Remove => {
+                let accessor = self.pop_stack()?;
+                let target = self.pop_stack()?;
+                self.stack
+                    .push(remove_key_from_collection(accessor, target)?);
+            }
+            Command::RemoveSlice(start, end, step) => {
+                let bounds = self.pop_slice_bounds(start, end, step)?;
+                let target = self.pop_stack()?;
+                self.stack.push(remove_slice(bounds, target)?);
+            }
+            Command::Push => {
+                let value = self.pop_stack()?;
+                let target = self.pop_stack()?;
+                push_to_collection(target, value)?;
+            }
+            Command::Insert => {
+                let value = self.pop_stack()?;
+                let accessor = self.pop_stack()?;
+                let target = self.pop_stack()?;
+                insert_in_collection(accessor, target, value)?;
+            }
+            Command::Pop => {
+                let target = self.pop_stack()?;
+                self.stack.push(pop_from_collection(target)?);
+            }
+            Command::Iterable => {
+                let target = self.pop_stack()?;
+                self.stack.push(get_iterable(target)?);
+            }
+            Command::MatchLength(count, rest) => {
+                let target = self.pop_stack()?;
+                match_length(&target, count, rest)?;
+            }
+            Command::RestFrom(index) => {
+                let target = self.pop_stack()?;
+                self.stack.push(rest_from(&target, index)?);
+            }
             Command::Len => {
                 let target = self.pop_stack()?;
-                if !(target.is_string() || target.is_array()) {
+                if !(target.is_string() || target.is_array() || target.is_dict()) {
                     return Err(RuntimeError::CannotGetLengthOfType);
                 }
                 self.stack.push(DataType::Integer(target.len() as i64));
@@ -128,6 +628,17 @@ impl Vm {
                 let target = self.pop_stack()?;
                 self.stack.push(get_item_in_collection(accessor, target)?);
             }
+            Command::Slice(start, end, step) => {
+                let bounds = self.pop_slice_bounds(start, end, step)?;
+                let target = self.pop_stack()?;
+                self.stack.push(get_slice(bounds, target)?);
+            }
+            Command::SliceAssign(start, end, step) => {
+                let value = self.pop_stack()?;
+                let bounds = self.pop_slice_bounds(start, end, step)?;
+                let target = self.pop_stack()?;
+                update_slice(bounds, target, value)?;
+            }
             Command::AccessAssign => {
                 let value = self.pop_stack()?;
                 let accessor = self.pop_stack()?;
@@ -137,68 +648,104 @@ impl Vm {
             Command::Equals => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
-
-                let b = if left.is_bool() && right.is_bool() {
-                    left.as_bool() == right.as_bool()
-                } else if left.is_int() && right.is_int() {
-                    left.as_int() == right.as_int()
-                } else if left.is_float() && right.is_float() {
-                    left.as_float() == right.as_float()
-                } else {
-                    return Err(RuntimeError::CannotCompareTypes);
-                };
-
+                self.stack.push(DataType::Bool(left.equals(&right)?));
+            }
+            Command::NotEquals => {
+                let right = self.pop_stack()?;
+                let left = self.pop_stack()?;
+                self.stack.push(DataType::Bool(!left.equals(&right)?));
+            }
+            Command::Identical => {
+                let right = self.pop_stack()?;
+                let left = self.pop_stack()?;
+                self.stack.push(DataType::Bool(left.is_identical(&right)));
+            }
+            Command::LessThan => {
+                let b = self.compare()? == Some(Ordering::Less);
                 self.stack.push(DataType::Bool(b));
             }
+            Command::LessEquals => {
+                let b = matches!(self.compare()?, Some(Ordering::Less | Ordering::Equal));
+                self.stack.push(DataType::Bool(b));
+            }
+            Command::GreaterThan => {
+                let b = self.compare()? == Some(Ordering::Greater);
+                self.stack.push(DataType::Bool(b));
+            }
+            Command::GreaterEquals => {
+                let b = matches!(self.compare()?, Some(Ordering::Greater | Ordering::Equal));
+                self.stack.push(DataType::Bool(b));
+            }
+            Command::Not => {
+                let value = self.pop_stack()?;
+                self.stack.push(DataType::Bool(!value.get_bool()?));
+            }
+            Command::Neg => {
+                let value = self.pop_stack()?;
+                let negated = match value {
+                    DataType::Integer(int) => match int.checked_neg() {
+                        Some(int) => DataType::Integer(int),
+                        None => DataType::from_big_int(-&BigInt::from_i64(int)),
+                    },
+                    DataType::BigInt(ref big) => DataType::from_big_int(-big),
+                    DataType::Float(float) => DataType::Float(-float),
+                    _ => return Err(RuntimeError::CannotNegateType),
+                };
+                self.stack.push(negated);
+            }
             Command::Add => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
-                self.stack.push(left + right);
+                self.stack.push((left + right)?);
             }
             Command::Sub => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
-                self.stack.push(left - right);
+                self.stack.push((left - right)?);
             }
             Command::Mul => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
-                self.stack.push(left * right);
+                self.stack.push((left * right)?);
             }
             Command::Div => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
-                self.stack.push(left / right);
+                self.stack.push((left / right)?);
             }
             Command::Rem => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
-                self.stack.push(left % right);
+                self.stack.push((left % right)?);
             }
             Command::Exp => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
 
-                let value = if left.is_int() && right.is_int() {
-                    DataType::Integer(left.as_int().pow(right.as_int() as u32))
-                } else if left.is_float() && right.is_int() {
-                    DataType::Float(left.as_float().powi(right.as_int() as i32))
-                } else {
-                    DataType::Float(left.as_float().powf(right.as_float()))
-                };
-                self.stack.push(value);
+                self.stack.push(left.pow(&right)?);
             }
             Command::Concat => {
                 let right = self.pop_stack()?;
                 let left = self.pop_stack()?;
                 if left.is_string() && right.is_string() {
-                    let left = left.as_string();
-                    let right = right.as_string();
-                    left.borrow_mut().push_str(right.borrow().as_str());
+                    // copy first as both sides can be the same string
+                    let right = right.as_string().borrow().clone();
+                    left.as_string().borrow_mut().push_str(&right);
+                } else if left.is_array() && right.is_array() {
+                    let right = right.get_array()?.borrow().clone();
+                    left.get_array()?.borrow_mut().extend(right);
                 } else {
                     return Err(RuntimeError::CannotConcat);
                 }
             }
+            Command::Jump(to) => {
+                current_calls.command_index += to;
+                return Ok((None, false, None));
+            }
+            Command::JumpBack(to) => {
+                current_calls.command_index -= to;
+                return Ok((None, false, None));
+            }
             Command::JumpIfFalse(to) => {
                 let cmp = self.pop_stack()?;
 
@@ -207,61 +754,90 @@ impl Vm {
                     return Ok((None, false, None));
                 }
             }
-            Command::Call => {
+            Command::StackLen => self.stack.push(DataType::Integer(self.stack.len() as i64)),
+            Command::TruncateStack => {
+                let len = self.pop_stack()?;
+                if !len.is_int() || len.as_int() < 0 {
+                    return Err(RuntimeError::InvalidStackLength);
+                }
+                self.stack.truncate(len.as_int() as usize);
+            }
+            Command::Break | Command::Continue => return Err(RuntimeError::LoopControlOutsideLoop),
+            Command::Call(given, ref names) => {
                 let function = self.pop_stack()?;
-                let (body, num_args) = function.get_function()?;
-
-                current_calls.command_index += 1;
-
-                if self.stack.len() < num_args {
-                    return Err(RuntimeError::InvalidNumberOfArguments);
+                if let DataType::NativeFunction(ref native) = function {
+                    return self.call_native(current_calls, native, given, names);
                 }
-
-                let new_calls = CallInfo {
-                    commands: body,
-                    num_args: num_args,
-                    stack_index: self.stack.len() - num_args,
-                    command_index: 0,
-                    locals: Vec::new(),
-                };
-
-                return Ok((Some(new_calls), false, None));
+                let (body, arity, upvalues) = function.get_function()?;
+                return self.call(current_calls, body, arity, upvalues, given, names);
             }
-            Command::CallSelf => {
-                let num_args = current_calls.num_args;
-
-                current_calls.command_index += 1;
-
-                if self.stack.len() < num_args {
-                    return Err(RuntimeError::InvalidNumberOfArguments);
+            Command::TailCall(given, ref names) => {
+                let function = self.pop_stack()?;
+                if let DataType::NativeFunction(ref native) = function {
+                    return self.call_native(current_calls, native, given, names);
                 }
-
-                let new_calls = CallInfo {
-                    commands: Rc::clone(&current_calls.commands),
-                    num_args: num_args,
-                    stack_index: self.stack.len() - num_args,
-                    command_index: 0,
-                    locals: Vec::new(),
+                let (body, arity, upvalues) = function.get_function()?;
+                if globals.is_none() {
+                    return self.call(current_calls, body, arity, upvalues, given, names);
+                }
+                return self.tail_call(current_calls, body, arity, upvalues, given, names);
+            }
+            Command::Import(slot, ref commands) => {
+                current_calls.command_index += 1;
+                let namespace = match globals {
+                    Some(globals) => globals.get(slot).cloned(),
+                    None => current_calls.locals.get(slot).cloned(),
                 };
-
+                if let Some(Some(namespace)) = namespace {
+                    self.stack.push(namespace);
+                    return Ok((None, false, None));
+                }
+                let new_calls =
+                    CallInfo::new(Rc::clone(commands), Rc::new(Arity::new()), self.stack.len());
                 return Ok((Some(new_calls), false, None));
             }
+            Command::CallSelf(given, ref names) => {
+                let body = Rc::clone(&current_calls.commands);
+                let arity = Rc::clone(&current_calls.arity);
+                let upvalues = current_calls.upvalues.clone();
+                return self.call(current_calls, body, arity, upvalues, given, names);
+            }
+            Command::TailCallSelf(given, ref names) => {
+                let body = Rc::clone(&current_calls.commands);
+                let arity = Rc::clone(&current_calls.arity);
+                let upvalues = current_calls.upvalues.clone();
+                if globals.is_none() {
+                    return self.call(current_calls, body, arity, upvalues, given, names);
+                }
+                return self.tail_call(current_calls, body, arity, upvalues, given, names);
+            }
+            Command::ArgMissing(index) => {
+                let missing = current_calls.missing.contains(&index);
+                self.stack.push(DataType::Bool(missing));
+            }
             Command::LoadStackArg(index) => {
                 let stack_index = current_calls.stack_index + index;
-                let value = match self.stack.get(stack_index) {
-                    Some(val) => val.clone(),
+                let value = match current_calls.arg_cell(index) {
+                    Some(cell) => cell.borrow().clone(),
+                    None => self.stack.get(stack_index).cloned(),
+                };
+                match value {
+                    Some(value) => self.stack.push(value),
                     None => return Err(RuntimeError::CannotLoadArgToStack(stack_index)),
                 };
-                self.stack.push(value);
             }
             Command::SaveStackArg(index) => {
                 let value = self.pop_stack()?;
-                let stack_index = current_calls.stack_index + index;
-                let stack_item = match self.stack.get_mut(stack_index) {
-                    Some(stack_item) => stack_item,
-                    None => return Err(RuntimeError::CannotSaveToStackIndex(stack_index)),
-                };
-                *stack_item = value;
+                if let Some(cell) = current_calls.arg_cell(index) {
+                    *cell.borrow_mut() = Some(value);
+                } else {
+                    let stack_index = current_calls.stack_index + index;
+                    let stack_item = match self.stack.get_mut(stack_index) {
+                        Some(stack_item) => stack_item,
+                        None => return Err(RuntimeError::CannotSaveToStackIndex(stack_index)),
+                    };
+                    *stack_item = value;
+                }
             }
             Command::Return => {
                 let mut save = None;
@@ -314,9 +890,53 @@ impl Vm {
                     }
                 }
             }
-            Command::Halt(code) => return Ok((None, false, Some(code))),
+            Command::TryStart(to) => {
+                current_calls.handlers.push(Handler {
+                    stack_len: self.stack.len(),
+                    command_index: current_calls.command_index + to,
+                });
+            }
+            Command::TryEnd => {
+                current_calls.handlers.pop();
+            }
+            Command::Throw => {
+                let value = self.pop_stack()?;
+                if value.is_dict() {
+                    return Err(RuntimeError::Thrown(value));
+                }
+                return Err(RuntimeError::Thrown(error_dict("Thrown", value)));
+            }
+            Command::Halt(code) => {
+                current_calls.handlers.clear();
+                return Ok((None, false, Some(code)));
+            }
         };
         current_calls.command_index += 1;
         Ok((None, false, None))
     }
 }
+
+fn upvalue(current_calls: &CallInfo, index: usize) -> Option<SharedCell> {
+    match current_calls.upvalues {
+        Some(ref upvalues) => upvalues.get(index).cloned(),
+        None => None,
+    }
+}
+
+fn error_dict(kind: &str, message: DataType) -> DataType {
+    let mut dict = BTreeMap::new();
+    dict.insert(
+        DictKey::String("kind".to_string()),
+        DataType::String(Rc::new(RefCell::new(kind.to_string()))),
+    );
+    dict.insert(DictKey::String("message".to_string()), message);
+    DataType::Dict(Rc::new(RefCell::new(dict)))
+}
+
+fn error_to_value(error: RuntimeError) -> DataType {
+    if let RuntimeError::Thrown(value) = error {
+        return value;
+    }
+    let message = DataType::String(Rc::new(RefCell::new(error.message())));
+    error_dict(&error.kind(), message)
+}
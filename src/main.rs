@@ -6,7 +6,13 @@ use kscript::lang::Kscript;
 fn main() {
     let mut kscript = Kscript::new();
 
-    let exit_code = kscript.run_from_args().unwrap();
+    let exit_code = match kscript.run_from_args() {
+        Ok(exit_code) => exit_code,
+        Err(error) => {
+            eprintln!("{:?}", error);
+            1
+        }
+    };
 
     process::exit(exit_code);
 }
@@ -0,0 +1,31 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+const TOTAL: &str = "
+total = .first,...rest {
+    sum = first
+    rest @@ x { sum = sum + x }
+    sum
+}";
+
+#[test]
+fn a_rest_arg_collects_the_extra_args() {
+    assert_eq!(global(&format!("{}\nx = total.1;", TOTAL), "x"), "1");
+    assert_eq!(global(&format!("{}\nx = total.1,2,3;", TOTAL), "x"), "6");
+    assert_eq!(global("items = ....rest { rest }\nx = items.1,2;", "x"), "12");
+}
+
+#[test]
+fn too_few_or_too_many_args_are_an_error() {
+    assert!(error("add = .x,y { x + y }\nz = add.1;").contains("WrongNumberOfArguments"));
+    assert!(error("add = .x,y { x + y }\nz = add.1,2,3;").contains("WrongNumberOfArguments"));
+    assert!(error(&format!("{}\nx = total.;", TOTAL)).contains("WrongNumberOfArguments"));
+}
+
+#[test]
+fn natives_check_their_args_too() {
+    assert!(error("x = len.1,2;").contains("WrongNumberOfArguments"));
+}
@@ -0,0 +1,51 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn results_past_64_bit_carry_on() {
+    assert_eq!(global("x = 9223372036854775807 + 1", "x"), "9223372036854775808");
+    assert_eq!(global("x = -9223372036854775807 - 2", "x"), "-9223372036854775809");
+    assert_eq!(global("x = 4611686018427387904 * 4", "x"), "18446744073709551616");
+    assert_eq!(global("x = 2 ** 63", "x"), "9223372036854775808");
+}
+
+#[test]
+fn dividing_the_smallest_integer_by_minus_one_carries_on() {
+    assert_eq!(global("x = (-9223372036854775807 - 1) / -1", "x"), "9223372036854775808");
+    assert_eq!(global("x = (-9223372036854775807 - 1) // -1", "x"), "0");
+}
+
+#[test]
+fn integer_math_stays_integer() {
+    assert_eq!(global("x = 7 / 2", "x"), "3");
+    assert_eq!(global("x = 7 // 3", "x"), "1");
+    assert_eq!(global("x = type.7 / 2;", "x"), "int");
+}
+
+#[test]
+fn a_float_makes_the_math_float() {
+    assert_eq!(global("x = 7 / 2.0", "x"), "3.5");
+    assert_eq!(global("x = type.1 + 1.0;", "x"), "float");
+}
+
+#[test]
+fn a_negative_power_of_an_integer_is_an_error() {
+    assert!(error("x = 2 ** -1").contains("NegativeExponent"));
+}
+
+#[test]
+fn a_power_too_big_to_hold_is_an_overflow() {
+    assert!(error("x = 2 ** 9999999999").contains("Overflow"));
+    let program = "kind = \"none\"\ntry { x = 2 ** 9999999999 } catch e { kind = e[\"kind\"] }";
+    assert_eq!(global(program, "kind"), "Overflow");
+}
+
+#[test]
+fn float_powers_do_not_cut_the_exponent() {
+    assert_eq!(global("x = 2.0 ** 4294967296", "x"), "inf");
+    assert_eq!(global("x = 2.0 ** 3", "x"), "8");
+    assert_eq!(global("x = 4 ** 0.5", "x"), "2");
+}
@@ -0,0 +1,70 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn literals_past_64_bit_are_big_integers() {
+    assert_eq!(global("x = 100000000000000000000", "x"), "100000000000000000000");
+    assert_eq!(global("x = type.100000000000000000000;", "x"), "int");
+}
+
+#[test]
+fn big_integer_math() {
+    assert_eq!(global("x = 2 ** 100", "x"), "1267650600228229401496703205376");
+    assert_eq!(global("x = 2 ** 100 - 2 ** 100 + 1", "x"), "1");
+    assert_eq!(global("x = 2 ** 100 / 3", "x"), "422550200076076467165567735125");
+    assert_eq!(global("x = 2 ** 100 // 7", "x"), "2");
+    assert_eq!(global("x = -(2 ** 70) / 3", "x"), "-393530540239137101141");
+}
+
+#[test]
+fn big_integers_go_back_to_64_bit_when_they_fit() {
+    assert_eq!(global("x = 2 ** 100 / 2 ** 98", "x"), "4");
+    assert_eq!(global("x = 9223372036854775807 + 1 - 1", "x"), "9223372036854775807");
+}
+
+#[test]
+fn big_integers_mix_with_floats() {
+    assert_eq!(global("x = 2 ** 64 + 0.5", "x"), "18446744073709552000");
+    assert_eq!(global("x = 2 ** 64 >? 1.5", "x"), "t");
+}
+
+#[test]
+fn big_integers_compare() {
+    assert_eq!(global("x = 2 ** 64 == 2 ** 64", "x"), "t");
+    assert_eq!(global("x = 2 ** 64 < 2 ** 65", "x"), "t");
+    assert_eq!(global("x = -(2 ** 64) < 2 ** 64", "x"), "t");
+}
+
+#[test]
+fn big_exponents_of_zero_and_one_are_exact() {
+    assert_eq!(global("x = (0 - 1) ** (2 ** 70)", "x"), "1");
+    assert_eq!(global("x = (0 - 1) ** (2 ** 70 + 1)", "x"), "-1");
+    assert_eq!(global("x = 1 ** (2 ** 70)", "x"), "1");
+    assert_eq!(global("x = 0 ** (2 ** 70)", "x"), "0");
+    assert_eq!(global("x = type.1 ** (2 ** 70);", "x"), "int");
+    assert_eq!(global("g = .x { x ** (2 ** 64) }\nx = g.1;", "x"), "1");
+}
+
+#[test]
+fn big_exponents_of_other_integers_are_an_overflow() {
+    assert!(error("x = 2 ** (2 ** 70)").contains("Overflow"));
+    assert!(error("x = (2 ** 70) ** (2 ** 64)").contains("Overflow"));
+    assert!(error("x = 2 ** -(2 ** 70)").contains("NegativeExponent"));
+}
+
+#[test]
+fn powers_too_big_to_make_quickly_are_an_overflow() {
+    assert!(error("x = 2 ** 40000000").contains("Overflow"));
+    assert!(error("x = 2 ** 4000000000").contains("Overflow"));
+    assert!(error("x = (2 ** 100) ** 3000").contains("Overflow"));
+    assert_eq!(global("x = len.str.2 ** 10000;;", "x"), "3011");
+}
+
+#[test]
+fn big_integer_bases_with_small_exponents() {
+    assert_eq!(global("x = (2 ** 70) ** 2", "x"), "1393796574908163946345982392040522594123776");
+    assert_eq!(global("x = (-(2 ** 64)) ** 3 == -(2 ** 192)", "x"), "t");
+}
@@ -0,0 +1,58 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global, run};
+use kscript::lang::{DataType, NativeFunction};
+
+#[test]
+fn len_counts_items() {
+    assert_eq!(global("n = len.@[1, 2, 3];", "n"), "3");
+    assert_eq!(global("n = len.@{\"a\": 1};", "n"), "1");
+    assert!(error("n = len.1;").contains("CannotGetLengthOfType"));
+}
+
+#[test]
+fn string_length_is_in_chars() {
+    assert_eq!(global("n = len.\"héllo\";", "n"), "5");
+    assert_eq!(global("n = @? \"héllo\"", "n"), "5");
+    assert_eq!(global("s = \"日本\"\nn = len.s;", "n"), "2");
+}
+
+#[test]
+fn type_names_the_type() {
+    assert_eq!(global("x = type.2.5;", "x"), "float");
+    assert_eq!(global("x = type.@[];", "x"), "array");
+    assert_eq!(global("x = type.\"ab\";", "x"), "string");
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(global("x = int.\"41\"; + 1", "x"), "42");
+    assert_eq!(global("x = int.-2.7;", "x"), "-2");
+    assert_eq!(global("x = float.\"1.5\"; * 2", "x"), "3");
+    assert_eq!(global("x = str.3; + \"!\"", "x"), "3!");
+    assert!(error("x = int.\"four\";").contains("InvalidConversion"));
+}
+
+#[test]
+fn int_reads_big_integers() {
+    assert_eq!(global("x = int.\"-100000000000000000000\";", "x"), "-100000000000000000000");
+}
+
+#[test]
+fn variables_hide_builtins() {
+    assert_eq!(global("len = 4\nx = len", "x"), "4");
+}
+
+#[test]
+fn natives_added_from_rust_are_called_like_functions() {
+    let mut kscript = kscript::lang::Kscript::new();
+    kscript.add_native(NativeFunction::new("double", &["x"], false, |args| {
+        Ok(Some(DataType::Integer(args.arg(0)?.as_int() * 2)))
+    }));
+    kscript.run_string("x = double.21;\n").unwrap();
+    assert_eq!(kscript.get_global("x").unwrap().to_string(), "42");
+    // a program without it still has the prelude
+    assert_eq!(run("x = len.\"ab\";").get_global("x").unwrap().to_string(), "2");
+}
@@ -0,0 +1,120 @@
+extern crate kscript;
+
+mod common;
+
+use common::global;
+
+#[test]
+fn closure_captures_enclosing_args() {
+    let program = "adder = .x { .y { x + y } }\nadd5 = adder.5;\nr = add5.3;";
+    assert_eq!(global(program, "r"), "8");
+}
+
+#[test]
+fn closure_keeps_state_between_calls() {
+    let program = "
+make_counter = .start {
+    count = start
+    .{
+        count = count + 1
+        count
+    }
+}
+c = make_counter.10;
+c.;
+d = make_counter.0;
+d.;
+r = c.;";
+    assert_eq!(global(program, "r"), "12");
+}
+
+#[test]
+fn closure_writes_reach_the_enclosing_function() {
+    let program = "mk = .{\n    n = 0\n    inc = .{ n = n + 1 }\n    inc.;\n    n\n}\nr = mk.;";
+    assert_eq!(global(program, "r"), "1");
+}
+
+#[test]
+fn closures_made_together_share_vars() {
+    let program = "
+pair = .{
+    count = 0
+    up = .{ count = count + 1 }
+    get = .{ count }
+    @[up, get]
+}
+p = pair.;
+up = p[0]
+get = p[1]
+up.;
+up.;
+r = get.;";
+    assert_eq!(global(program, "r"), "2");
+}
+
+#[test]
+fn closure_sees_later_changes() {
+    let program = "late = .{\n    x = 1\n    read = .{ x }\n    x = 5\n    read.\n}\nr = late.;";
+    assert_eq!(global(program, "r"), "5");
+}
+
+#[test]
+fn closure_shares_captured_args() {
+    let program = "h = .a {\n    bump = .{ a = a + 1 }\n    bump.;\n    bump.;\n    a\n}\nr = h.10;";
+    assert_eq!(global(program, "r"), "12");
+}
+
+#[test]
+fn local_closure_calls_itself() {
+    let program = "
+h = .{
+    fact = .n { n == 0 ? { 1 ;; } n * fact.n - 1; }
+    fact.5;
+}
+r = h.;";
+    assert_eq!(global(program, "r"), "120");
+}
+
+#[test]
+fn nested_closures_share_through_each_level() {
+    let program = "
+deep = .{
+    v = 1
+    outer = .{
+        inner = .{ v = v + 10 }
+        inner.;
+        v
+    }
+    r = outer.;
+    r + v
+}
+r = deep.;";
+    assert_eq!(global(program, "r"), "22");
+}
+
+#[test]
+fn each_run_of_a_loop_has_its_own_item() {
+    let program = "
+make = .{
+    out = @[]
+    @[1, 2, 3] @@ x { out +[] .{ x } }
+    out
+}
+fs = make.;
+first = fs[0]
+r = first.;";
+    assert_eq!(global(program, "r"), "1");
+}
+
+#[test]
+fn top_level_loop_items_are_globals() {
+    let program = "
+out = @[]
+@[1, 2, 3] @@ x { out +[] .{ x } }
+seen = \"\"
+out @@ g {
+    v = g.;
+    seen ++ str.v;
+}";
+    assert_eq!(global(program, "seen"), "333");
+}
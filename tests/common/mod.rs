@@ -0,0 +1,34 @@
+#![allow(dead_code)]
+
+use kscript::lang::{DataType, Kscript};
+
+// runs the program and gives back the kscript so its globals can be read
+pub fn run(program: &str) -> Kscript {
+    let mut kscript = Kscript::new();
+    let program = format!("{}\n", program);
+    if let Err(error) = kscript.run_string(&program) {
+        panic!("{:?} running {}", error, program);
+    }
+    kscript
+}
+
+pub fn get(kscript: &Kscript, name: &str) -> DataType {
+    match kscript.get_global(name) {
+        Some(value) => value,
+        None => panic!("no global {}", name),
+    }
+}
+
+// the global written out the same as >> writes it
+pub fn global(program: &str, name: &str) -> String {
+    get(&run(program), name).to_string()
+}
+
+// the error the program stops with written out with {:?}
+pub fn error(program: &str) -> String {
+    let mut kscript = Kscript::new();
+    match kscript.run_string(&format!("{}\n", program)) {
+        Ok(_) => panic!("no error running {}", program),
+        Err(error) => format!("{:?}", error),
+    }
+}
@@ -0,0 +1,45 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn integers_and_floats_order() {
+    assert_eq!(global("x = 1 < 2", "x"), "t");
+    assert_eq!(global("x = 2 < 2", "x"), "f");
+    assert_eq!(global("x = 2 <= 2", "x"), "t");
+    assert_eq!(global("x = 3 >? 2", "x"), "t");
+    assert_eq!(global("x = 2.5 >= 3.0", "x"), "f");
+    assert_eq!(global("x = 2 < 2.5", "x"), "t");
+}
+
+#[test]
+fn not_equal_is_the_opposite_of_equal() {
+    assert_eq!(global("x = 1 != 2", "x"), "t");
+    assert_eq!(global("x = \"ab\" != \"ab\"", "x"), "f");
+    assert_eq!(global("x = 1 != \"1\"", "x"), "t");
+}
+
+#[test]
+fn big_integers_order_with_the_others() {
+    assert_eq!(global("x = 2 ** 64 >? 1.5", "x"), "t");
+    assert_eq!(global("x = -(2 ** 64) < 1", "x"), "t");
+}
+
+#[test]
+fn comparisons_bind_looser_than_math() {
+    assert_eq!(global("x = 1 + 1 < 3", "x"), "t");
+    assert_eq!(global("x = 2 * 3 >= 7", "x"), "f");
+}
+
+#[test]
+fn only_numbers_and_chars_order() {
+    assert!(error("x = @[1] < @[2]").contains("RuntimeError"));
+}
+
+#[test]
+fn identity_compares_references() {
+    assert_eq!(global("a = @[1]\nx = a === @[1]", "x"), "f");
+    assert_eq!(global("a = @[1]\nb = a\nx = a === b", "x"), "t");
+}
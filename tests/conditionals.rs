@@ -0,0 +1,41 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn if_runs_block_when_true() {
+    assert_eq!(global("x = 1\n1 == 1 ? { x = 2 }", "x"), "2");
+    assert_eq!(global("x = 1\n1 == 2 ? { x = 2 }", "x"), "1");
+}
+
+#[test]
+fn else_runs_when_false() {
+    assert_eq!(global("x = 1 == 2 ? { 5 } : { 6 }", "x"), "6");
+    assert_eq!(global("x = 1 == 1 ? { 5 } : { 6 }", "x"), "5");
+}
+
+#[test]
+fn else_if_chain_picks_first_true_branch() {
+    let program = "x = 5\ny = x == 1 ? { \"one\" } : x == 5 ? { \"five\" } : { \"other\" }";
+    assert_eq!(global(program, "y"), "five");
+    let program = "x = 7\ny = x == 1 ? { \"one\" } : x == 5 ? { \"five\" } : { \"other\" }";
+    assert_eq!(global(program, "y"), "other");
+}
+
+#[test]
+fn if_without_else_as_value_is_rejected() {
+    let expected = "ParserError(IfWithoutElseAsValue)";
+    assert_eq!(error("v = 1 == 2 ? { 5 }"), expected);
+    assert_eq!(error("v = @[1 == 2 ? { 5 }]"), expected);
+    assert_eq!(error("v = (1 == 2 ? { 5 })"), expected);
+    assert_eq!(error("v = 1 == 2 ? { 5 } : 1 == 1 ? { 6 }"), expected);
+}
+
+#[test]
+fn if_without_else_in_function_gives_nothing() {
+    let program = "g = .x { x == 1 ? { 5 ;; } 6 }\na = g.1;\nb = g.2;";
+    assert_eq!(global(program, "a"), "5");
+    assert_eq!(global(program, "b"), "6");
+}
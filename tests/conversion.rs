@@ -0,0 +1,87 @@
+extern crate kscript;
+
+use std::collections::HashMap;
+use kscript::lang::{DataType, FromKscript, IntoKscript, Kscript, NativeFunction, RuntimeError};
+
+fn script_value(program: &str) -> DataType {
+    let mut kscript = Kscript::new();
+    kscript.run_string(&format!("x = {}\n", program)).unwrap();
+    kscript.get_global("x").unwrap()
+}
+
+#[test]
+fn rust_values_into_script_values() {
+    assert_eq!(true.into_kscript().to_string(), "t");
+    assert_eq!(3i64.into_kscript().to_string(), "3");
+    assert_eq!(2.5f64.into_kscript().to_string(), "2.5");
+    assert_eq!("ab".into_kscript().to_string(), "ab");
+    assert_eq!(vec![1i64, 2].into_kscript().to_string(), "12");
+    assert_eq!((1i64, "a", false).into_kscript().to_string(), "1af");
+    let mut map = HashMap::new();
+    map.insert("a".to_string(), 1i64);
+    assert_eq!(map.into_kscript().to_string(), "{a: 1}");
+}
+
+#[test]
+fn script_values_into_rust_values() {
+    assert_eq!(i64::from_kscript(script_value("40 + 2")).unwrap(), 42);
+    assert_eq!(f64::from_kscript(script_value("1")).unwrap(), 1.0);
+    assert_eq!(String::from_kscript(script_value("\"ab\" + \"c\"")).unwrap(), "abc");
+    assert_eq!(String::from_kscript(script_value("\"a\"")).unwrap(), "a");
+    assert_eq!(Vec::<i64>::from_kscript(script_value("@[1, 2]")).unwrap(), vec![1, 2]);
+    let pair: (i64, String) = FromKscript::from_kscript(script_value("@[1, \"ab\"]")).unwrap();
+    assert_eq!(pair, (1, "ab".to_string()));
+    let map: HashMap<String, i64> = FromKscript::from_kscript(script_value("@{\"a\": 1, 2: 3}")).unwrap();
+    assert_eq!(map["a"], 1);
+    assert_eq!(map["2"], 3);
+}
+
+#[test]
+fn values_of_another_type_are_wrong_type() {
+    match i64::from_kscript(script_value("\"ab\"")) {
+        Err(RuntimeError::WrongType(expected, _)) => assert_eq!(expected, "int"),
+        other => panic!("{:?}", other),
+    }
+    assert!(Vec::<i64>::from_kscript(script_value("@[1, \"ab\"]")).is_err());
+}
+
+#[test]
+fn big_integers_do_not_fit_in_i64() {
+    match i64::from_kscript(script_value("2 ** 64")) {
+        Err(RuntimeError::Overflow) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn tuples_need_the_same_number_of_items() {
+    let pair: Result<(i64, i64), _> = FromKscript::from_kscript(script_value("@[1, 2, 3]"));
+    match pair {
+        Err(RuntimeError::ShapeMismatch(2, 3)) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn natives_from_closures_convert_their_args() {
+    let mut kscript = Kscript::new();
+    kscript.add_native(NativeFunction::from_fn(
+        "repeat",
+        &["text", "times"],
+        |text: String, times: Option<i64>| text.repeat(times.unwrap_or(2) as usize),
+    ));
+    kscript.add_native(NativeFunction::from_fn("half", &["x"], |x: i64| {
+        if x % 2 == 1 {
+            return Err(RuntimeError::InvalidConversion);
+        }
+        Ok(x / 2)
+    }));
+    kscript.run_string("a = repeat.\"ab\";\nb = repeat.\"ab\",3;\nc = repeat.times: 1,text: \"ab\";\n").unwrap();
+    assert_eq!(kscript.get_global("a").unwrap().to_string(), "abab");
+    assert_eq!(kscript.get_global("b").unwrap().to_string(), "ababab");
+    assert_eq!(kscript.get_global("c").unwrap().to_string(), "ab");
+    kscript.run_string("d = half.4;\nkind = \"none\"\ntry { half.3; } catch e { kind = e[\"kind\"] }\n").unwrap();
+    assert_eq!(kscript.get_global("d").unwrap().to_string(), "2");
+    assert_eq!(kscript.get_global("kind").unwrap().to_string(), "InvalidConversion");
+    assert!(kscript.run_string("e = half.\"ab\";\n").is_err());
+}
@@ -0,0 +1,53 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn names_take_one_item_each() {
+    let program = "x, y = @[1, 2]";
+    assert_eq!(global(program, "x"), "1");
+    assert_eq!(global(program, "y"), "2");
+}
+
+#[test]
+fn a_rest_name_takes_the_items_left() {
+    let program = "first, ...rest = @[1, 2, 3]";
+    assert_eq!(global(program, "first"), "1");
+    assert_eq!(global(program, "rest"), "23");
+    assert_eq!(global("first, ...rest = @[1]\nn = @? rest", "n"), "0");
+}
+
+#[test]
+fn groups_take_apart_nested_arrays() {
+    let program = "first, (a, b), ...rest = @[1, @[2, 3], 4, 5]";
+    assert_eq!(global(program, "a"), "2");
+    assert_eq!(global(program, "b"), "3");
+    assert_eq!(global(program, "rest"), "45");
+}
+
+#[test]
+fn swapping_through_an_array() {
+    let program = "x = 1\ny = 2\nx, y = @[y, x]";
+    assert_eq!(global(program, "x"), "2");
+    assert_eq!(global(program, "y"), "1");
+}
+
+#[test]
+fn destructuring_works_in_functions() {
+    let program = "sum = .pair { a, b = pair\na + b }\nx = sum.@[3, 4];";
+    assert_eq!(global(program, "x"), "7");
+}
+
+#[test]
+fn the_wrong_number_of_items_is_a_shape_mismatch() {
+    assert!(error("x, y = @[1, 2, 3]").contains("ShapeMismatch"));
+    assert!(error("x, y = @[1]").contains("ShapeMismatch"));
+    assert!(error("x, (a, b) = @[1, @[2]]").contains("ShapeMismatch"));
+}
+
+#[test]
+fn only_arrays_can_be_taken_apart() {
+    assert!(error("x, y = 1").contains("RuntimeError"));
+}
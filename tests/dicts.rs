@@ -0,0 +1,54 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn dicts_are_written_in_key_order() {
+    assert_eq!(global("d = @{\"b\": 2, \"a\": 1, 3: \"three\"}", "d"), "{3: three, a: 1, b: 2}");
+}
+
+#[test]
+fn get_and_set_by_key() {
+    assert_eq!(global("d = @{\"a\": 1}\nx = d[\"a\"]", "x"), "1");
+    assert_eq!(global("d = @{}\nd =[\"c\"] 5\nx = d[\"c\"]", "x"), "5");
+    assert_eq!(global("d = @{\"a\": 1}\nd =[\"a\"] 2\nx = @? d", "x"), "1");
+}
+
+#[test]
+fn char_keys_are_string_keys() {
+    // "a" is a char, slicing "ab" gives the string a
+    assert_eq!(global("d = @{\"a\": 1}\nk = \"ab\"[:1]\nx = d[k]", "x"), "1");
+}
+
+#[test]
+fn has_and_remove_keys() {
+    assert_eq!(global("d = @{\"a\": 1}\nx = d ?[\"a\"]", "x"), "t");
+    assert_eq!(global("d = @{\"a\": 1}\nx = d ?[\"b\"]", "x"), "f");
+    let program = "d = @{\"a\": 1, \"b\": 2}\nx = d -[\"a\"]\nn = @? d";
+    assert_eq!(global(program, "x"), "1");
+    assert_eq!(global(program, "n"), "1");
+}
+
+#[test]
+fn missing_keys_are_an_error() {
+    assert!(error("d = @{}\nx = d[\"a\"]").contains("RuntimeError"));
+}
+
+#[test]
+fn loops_go_over_the_keys_in_order() {
+    let program = "d = @{\"b\": 2, \"a\": 1}\nkeys = \"\"\nd @@ key { keys ++ key }";
+    assert_eq!(global(program, "keys"), "ab");
+}
+
+#[test]
+fn functions_in_dicts_are_called_by_key() {
+    let program = "ops = @{\"add\": .x,y { x + y }}\nx = ops.[\"add\"] 1,2;";
+    assert_eq!(global(program, "x"), "3");
+}
+
+#[test]
+fn dicts_are_shared_by_reference() {
+    assert_eq!(global("d = @{}\ne = d\ne =[1] 2\nx = @? d", "x"), "1");
+}
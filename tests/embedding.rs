@@ -0,0 +1,79 @@
+extern crate kscript;
+
+use kscript::lang::{DataType, Kscript, KscriptError};
+
+#[test]
+fn globals_set_from_rust_are_seen_by_scripts() {
+    let mut kscript = Kscript::new();
+    kscript.set_global("limit", DataType::Integer(10));
+    kscript.run_string("over = limit + 1\n").unwrap();
+    assert_eq!(kscript.get_global("over").unwrap().to_string(), "11");
+}
+
+#[test]
+fn get_global_is_none_for_names_not_set() {
+    let mut kscript = Kscript::new();
+    assert!(kscript.get_global("missing").is_none());
+    kscript.run_string("x = 1\n").unwrap();
+    assert!(kscript.get_global("missing").is_none());
+    assert!(kscript.get_global("len").is_some());
+}
+
+#[test]
+fn call_runs_a_script_function() {
+    let mut kscript = Kscript::new();
+    kscript.set_global("limit", DataType::Integer(10));
+    kscript.run_string("over = .x { x >? limit }\n").unwrap();
+    let over = kscript.call("over", vec![DataType::Integer(12)]).unwrap();
+    assert_eq!(over.to_string(), "t");
+}
+
+#[test]
+fn call_keeps_the_changes_to_globals() {
+    let mut kscript = Kscript::new();
+    kscript.run_string("count = 0\nbump = .by { global count = count + by\ncount }\n").unwrap();
+    kscript.call("bump", vec![DataType::Integer(2)]).unwrap();
+    kscript.call("bump", vec![DataType::Integer(3)]).unwrap();
+    assert_eq!(kscript.get_global("count").unwrap().to_string(), "5");
+    kscript.run_string("after = count * 2\n").unwrap();
+    assert_eq!(kscript.get_global("after").unwrap().to_string(), "10");
+}
+
+#[test]
+fn call_can_call_natives() {
+    let mut kscript = Kscript::new();
+    let args = vec![DataType::Array(Default::default())];
+    assert_eq!(kscript.call("len", args).unwrap().to_string(), "0");
+}
+
+#[test]
+fn call_errors() {
+    let mut kscript = Kscript::new();
+    kscript.run_string("bad = .{ throw \"no\" }\nnothing = .{ x = 1 }\n").unwrap();
+    match kscript.call("missing", Vec::new()) {
+        Err(KscriptError::UnknownGlobal(name)) => assert_eq!(name, "missing"),
+        other => panic!("{:?}", other),
+    }
+    match kscript.call("bad", Vec::new()) {
+        Err(KscriptError::RuntimeError(_)) => {}
+        other => panic!("{:?}", other),
+    }
+    match kscript.call("nothing", Vec::new()) {
+        Err(KscriptError::NoReturnValue) => {}
+        other => panic!("{:?}", other),
+    }
+    match kscript.call("bad", vec![DataType::Integer(1)]) {
+        Err(KscriptError::RuntimeError(_)) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn the_globals_survive_a_failed_call() {
+    let mut kscript = Kscript::new();
+    kscript.run_string("n = 1\nbad = .{ global n = 2\nthrow \"no\" }\n").unwrap();
+    assert!(kscript.call("bad", Vec::new()).is_err());
+    assert_eq!(kscript.get_global("n").unwrap().to_string(), "2");
+    kscript.run_string("m = n + 1\n").unwrap();
+    assert_eq!(kscript.get_global("m").unwrap().to_string(), "3");
+}
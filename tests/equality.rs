@@ -0,0 +1,61 @@
+extern crate kscript;
+
+mod common;
+
+use common::global;
+
+#[test]
+fn arrays_and_dicts_compare_by_value() {
+    assert_eq!(global("e = @[1, @[2, 3]] == @[1, @[2, 3]]", "e"), "t");
+    assert_eq!(global("e = @[1, 2] == @[1, 2, 3]", "e"), "f");
+    assert_eq!(global("e = @{\"a\": 1} == @{\"a\": 1}", "e"), "t");
+    assert_eq!(global("e = @{\"a\": 1} == @{\"a\": 2}", "e"), "f");
+}
+
+#[test]
+fn values_of_different_types_are_not_equal() {
+    assert_eq!(global("e = 1 == \"1\"", "e"), "f");
+    assert_eq!(global("e = @[] == @{}", "e"), "f");
+}
+
+#[test]
+fn functions_are_only_equal_to_themselves() {
+    assert_eq!(global("g = .{ 1 }\nh = .{ 1 }\ne = g == g\nd = g == h", "e"), "t");
+    assert_eq!(global("g = .{ 1 }\nh = .{ 1 }\nd = g == h", "d"), "f");
+}
+
+#[test]
+fn arrays_holding_themselves_compare() {
+    let program = "
+a = @[1]
+a +[] a
+b = @[1]
+b +[] b
+c = @[2]
+c +[] c
+same = a == b
+other = a == c";
+    assert_eq!(global(program, "same"), "t");
+    assert_eq!(global(program, "other"), "f");
+}
+
+#[test]
+fn dicts_holding_themselves_compare() {
+    let program = "
+a = @{}
+a =[\"self\"] a
+b = @{}
+b =[\"self\"] b
+same = a == b";
+    assert_eq!(global(program, "same"), "t");
+}
+
+#[test]
+fn integers_equal_floats_exactly() {
+    assert_eq!(global("e = 2 == 2.0", "e"), "t");
+    assert_eq!(global("e = 2 == 2.5", "e"), "f");
+    assert_eq!(global("e = -3.0 == -3", "e"), "t");
+    assert_eq!(global("e = 9007199254740993 == 9007199254740992.0", "e"), "f");
+    assert_eq!(global("e = 9007199254740992 == 9007199254740992.0", "e"), "t");
+    assert_eq!(global("e = 100000000000000000000 == 100000000000000000000.0", "e"), "t");
+}
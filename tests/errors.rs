@@ -0,0 +1,115 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn catch_gets_the_error_as_a_dict() {
+    let program = "
+kind = \"none\"
+try {
+    a = @[1, 2]
+    a[5]
+} catch e {
+    kind = e[\"kind\"]
+}";
+    assert_eq!(global(program, "kind"), "CannotAccessWithAccessor");
+}
+
+#[test]
+fn catch_gets_a_message_for_faults() {
+    let program = "
+message = \"none\"
+try { 1 / 0 } catch e { message = e[\"message\"] }";
+    assert_eq!(global(program, "message"), "division by zero");
+}
+
+#[test]
+fn thrown_values_become_the_message() {
+    let program = "
+message = \"none\"
+try { throw \"bad\" } catch e { message = e[\"message\"] }";
+    assert_eq!(global(program, "message"), "bad");
+}
+
+#[test]
+fn finally_runs_when_nothing_is_thrown() {
+    let program = "done = 0\ntry { x = 1 } finally { done = done + 1 }";
+    assert_eq!(global(program, "done"), "1");
+}
+
+#[test]
+fn finally_runs_before_an_error_is_passed_on() {
+    let program = "
+done = 0
+try {
+    try { throw \"bad\" } finally { done = done + 1 }
+} catch e {
+    done = done + 10
+}";
+    assert_eq!(global(program, "done"), "11");
+}
+
+#[test]
+fn errors_in_functions_are_caught_by_the_caller() {
+    let program = "
+bad = .{ throw \"deep\" }
+message = \"none\"
+try { bad.; } catch e { message = e[\"message\"] }";
+    assert_eq!(global(program, "message"), "deep");
+}
+
+#[test]
+fn uncaught_errors_stop_the_program() {
+    assert!(error("throw \"bad\"").contains("Thrown"));
+}
+
+#[test]
+fn return_runs_the_finally() {
+    let program = "
+fin = 0
+g = .{ try { 1 ;; } finally { global fin = fin + 1 } 2 }
+n = g.;";
+    assert_eq!(global(program, "n"), "1");
+    assert_eq!(global(program, "fin"), "1");
+    let program = "
+fin = 0
+g = .{ try { throw \"x\" } catch { 3 ;; } finally { global fin = fin + 1\n9 } 2 }
+n = g.;";
+    assert_eq!(global(program, "n"), "3");
+    assert_eq!(global(program, "fin"), "1");
+}
+
+#[test]
+fn return_can_leave_a_try_without_finally() {
+    let program = "g = .{ try { throw \"x\" } catch { 3 ;; } 2 }\nn = g.;";
+    assert_eq!(global(program, "n"), "3");
+}
+
+#[test]
+fn loop_controls_run_the_finally() {
+    let program = "
+i = 0
+fin = 0
+i < 5 ?? { i = i + 1\ntry { i == 3 ? { break } } finally { fin = fin + 1 } }";
+    assert_eq!(global(program, "i"), "3");
+    assert_eq!(global(program, "fin"), "3");
+    let program = "
+odd = 0
+fin = 0
+@[1, 2, 3, 4, 5] @@ v {
+    try {
+        try { v // 2 == 0 ? { continue } } catch { x = 1 }
+        odd = odd + 1
+    } finally { fin = fin + 1 }
+}";
+    assert_eq!(global(program, "odd"), "3");
+    assert_eq!(global(program, "fin"), "5");
+}
+
+#[test]
+fn leaving_a_try_removes_its_catch() {
+    let program = "caught = 0\nt ?? { try { break } catch { caught = 1 } }\nthrow \"after\"";
+    assert!(error(program).contains("after"));
+}
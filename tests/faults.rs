@@ -0,0 +1,64 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+use kscript::lang::{Kscript, NativeFunction};
+
+#[test]
+fn integer_division_by_zero_is_an_error() {
+    assert!(error("x = 1 / 0").contains("DivisionByZero"));
+    assert!(error("x = 1 // 0").contains("DivisionByZero"));
+}
+
+#[test]
+fn reading_past_the_end_is_an_error() {
+    assert!(error("a = @[1, 2]\nx = a[5]").contains("RuntimeError"));
+}
+
+#[test]
+fn calling_a_value_that_is_not_a_function_is_an_error() {
+    assert!(error("g = 1\nx = g.;").contains("NotAFunction"));
+}
+
+#[test]
+fn negative_repeat_is_an_error() {
+    assert!(error("x = \"ab\" * -1").contains("NegativeRepeat"));
+}
+
+#[test]
+fn repeat_gives_the_string_that_many_times() {
+    assert_eq!(global("x = \"ab\" * 3", "x"), "ababab");
+    assert_eq!(global("x = \"\" * 9223372036854775807", "x"), "");
+}
+
+#[test]
+fn repeating_past_the_largest_string_is_an_overflow() {
+    assert!(error("x = \"ab\" * 9223372036854775807").contains("Overflow"));
+    assert!(error("x = \"ab\" * 2305843009213693951").contains("Overflow"));
+}
+
+#[test]
+fn faults_can_be_caught() {
+    let program = "
+kind = \"none\"
+try { x = \"ab\" * 9223372036854775807 } catch e { kind = e[\"kind\"] }";
+    assert_eq!(global(program, "kind"), "Overflow");
+}
+
+#[test]
+fn natives_reading_an_arg_not_given_get_an_error() {
+    let mut kscript = Kscript::new();
+    let mut first = NativeFunction::new("first", &["x"], false, |args| Ok(Some(args.arg(0)?.clone())));
+    first.arity.defaults = vec![true];
+    kscript.add_native(first);
+    kscript.add_native(NativeFunction::new("second", &["x"], false, |args| {
+        Ok(Some(args.arg(1)?.clone()))
+    }));
+    let error = kscript.run_string("x = first.;\n").unwrap_err();
+    assert!(format!("{:?}", error).contains("InvalidNumberOfArguments"));
+    let error = kscript.run_string("x = second.1;\n").unwrap_err();
+    assert!(format!("{:?}", error).contains("InvalidNumberOfArguments"));
+    kscript.run_string("x = first.2;\n").unwrap();
+    assert_eq!(kscript.get_global("x").map(|x| x.to_string()), Some("2".to_string()));
+}
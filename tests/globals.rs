@@ -0,0 +1,69 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn functions_read_and_assign_globals() {
+    let program = "calls = 0\ncount = .{ global calls = calls + 1 }\ncount.;\ncount.;";
+    assert_eq!(global(program, "calls"), "2");
+}
+
+#[test]
+fn functions_call_globals_defined_after_them() {
+    let program = "
+is_even = .n {
+    n == 0 ? { t ;; }
+    is_odd.n - 1;
+}
+is_odd = .n {
+    n == 0 ? { f ;; }
+    is_even.n - 1;
+}
+a = is_even.10;
+b = is_odd.7;";
+    assert_eq!(global(program, "a"), "t");
+    assert_eq!(global(program, "b"), "t");
+}
+
+#[test]
+fn global_never_set_is_an_error() {
+    let error = error("g = .{ later }\ng.;");
+    assert!(error.starts_with("RuntimeError(InvalidGlobalGetIndex("), "{}", error);
+}
+
+#[test]
+fn globals_survive_a_failed_run() {
+    let mut kscript = common::run("g = .{ later }\nzz = 5");
+    assert!(kscript.run_string("1/0\n").is_err());
+    kscript.run_string("later = 10\nyy = 6\n").unwrap();
+    assert_eq!(common::get(&kscript, "zz").to_string(), "5");
+    assert_eq!(common::get(&kscript, "later").to_string(), "10");
+    assert_eq!(common::get(&kscript, "yy").to_string(), "6");
+}
+
+#[test]
+fn assigning_in_a_function_makes_a_local() {
+    let before = "x = 1\nset = .{ x = 2\nx }\ny = set.;";
+    assert_eq!(global(before, "x"), "1");
+    assert_eq!(global(before, "y"), "2");
+    let after = "set = .{ x = 2\nx }\ny = set.;\nx = 1";
+    assert_eq!(global(after, "x"), "1");
+    assert_eq!(global(after, "y"), "2");
+}
+
+#[test]
+fn global_assigns_the_top_level_name() {
+    let before = "x = 1\nset = .{ global x = 2 }\nset.;";
+    assert_eq!(global(before, "x"), "2");
+    let after = "set = .{ global x = 2 }\nset.;\ny = x";
+    assert_eq!(global(after, "y"), "2");
+    let shadowed = "x = 1\nset = .x { global x = x + 1 }\nset.5;";
+    assert_eq!(global(shadowed, "x"), "6");
+}
+
+#[test]
+fn global_needs_a_name() {
+    assert_eq!(error("global 1"), "ParserError(InvalidGlobal)");
+}
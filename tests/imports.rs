@@ -0,0 +1,55 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn import_gives_the_top_level_names() {
+    let program = "shapes = import \"examples/modules/shapes\"\narea = shapes.[\"square\"] 4;";
+    assert_eq!(global(program, "area"), "16");
+}
+
+#[test]
+fn a_file_is_only_run_once() {
+    let program = "
+first = import \"examples/modules/counter\"
+again = import \"examples/modules/counter.ks\"
+first.[\"bump\"];
+n = again.[\"count\"];";
+    assert_eq!(global(program, "n"), "1");
+}
+
+#[test]
+fn the_namespace_follows_the_module() {
+    let program = "
+counter = import \"examples/modules/counter\"
+counter.[\"bump\"];
+counter.[\"bump\"];
+seen = counter[\"n\"]
+n = counter.[\"count\"];";
+    assert_eq!(global(program, "seen"), "2");
+    assert_eq!(global(program, "n"), "2");
+}
+
+#[test]
+fn setting_a_key_does_not_change_the_module() {
+    let program = "
+counter = import \"examples/modules/counter\"
+counter =[\"n\"] 10
+counter.[\"bump\"];
+seen = counter[\"n\"]
+n = counter.[\"count\"];";
+    assert_eq!(global(program, "seen"), "1");
+    assert_eq!(global(program, "n"), "1");
+}
+
+#[test]
+fn missing_modules_are_an_error() {
+    assert!(error("m = import \"tests/modules/missing\"").contains("ModuleNotFound"));
+}
+
+#[test]
+fn files_that_import_each_other_are_an_error() {
+    assert!(error("m = import \"tests/modules/cycle_a\"").contains("ImportCycle"));
+}
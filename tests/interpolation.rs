@@ -0,0 +1,39 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn braces_run_the_code_in_them() {
+    assert_eq!(global("a = 2\nb = 3\nx = \"sum: {a + b}\"", "x"), "sum: 5");
+    assert_eq!(global("x = \"{1}{2}\"", "x"), "12");
+}
+
+#[test]
+fn values_are_shown_as_they_are_written_out() {
+    assert_eq!(global("a = @[1, 2]\nx = \"a is {a}\"", "x"), "a is 12");
+    assert_eq!(global("x = \"{t}\"", "x"), "t");
+}
+
+#[test]
+fn escaped_braces_are_plain() {
+    assert_eq!(global("a = 2\nx = \"\\{a\\} is {a}\"", "x"), "{a} is 2");
+}
+
+#[test]
+fn calls_and_strings_inside_braces() {
+    assert_eq!(global("double = .x { x * 2 }\nx = \"{double.4;}\"", "x"), "8");
+    assert_eq!(global("x = \"<{\"ab\" + \"c\"}>\"", "x"), "<abc>");
+}
+
+#[test]
+fn each_run_makes_a_new_string() {
+    let program = "i = 0\nout = \"\"\ni < 3 ?? { out ++ \"{i}\"\ni = i + 1 }";
+    assert_eq!(global(program, "out"), "012");
+}
+
+#[test]
+fn an_unclosed_brace_is_an_error() {
+    assert!(error("x = \"{1\"").contains("ParserError"));
+}
@@ -0,0 +1,46 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn and_or_not() {
+    assert_eq!(global("x = t and t", "x"), "t");
+    assert_eq!(global("x = t and f", "x"), "f");
+    assert_eq!(global("x = f or t", "x"), "t");
+    assert_eq!(global("x = f or f", "x"), "f");
+    assert_eq!(global("x = not t", "x"), "f");
+    assert_eq!(global("x = !f", "x"), "t");
+}
+
+#[test]
+fn and_skips_the_right_side_when_the_left_is_false() {
+    let program = "calls = 0\nbump = .{ calls = calls + 1\nt }\nx = f and bump.;";
+    assert_eq!(global(program, "calls"), "0");
+    assert_eq!(global(program, "x"), "f");
+}
+
+#[test]
+fn or_skips_the_right_side_when_the_left_is_true() {
+    let program = "calls = 0\nbump = .{ calls = calls + 1\nf }\nx = t or bump.;";
+    assert_eq!(global(program, "calls"), "0");
+    assert_eq!(global(program, "x"), "t");
+}
+
+#[test]
+fn the_right_side_is_given_back_as_it_is() {
+    assert_eq!(global("x = t and 5", "x"), "5");
+    assert_eq!(global("x = f or \"ab\"", "x"), "ab");
+}
+
+#[test]
+fn logic_binds_looser_than_comparisons() {
+    assert_eq!(global("v = 5\nx = v >? 1 and v < 10", "x"), "t");
+    assert_eq!(global("v = 5\nx = not v == 5 or v == 4", "x"), "f");
+}
+
+#[test]
+fn the_left_side_must_be_a_bool() {
+    assert!(error("x = 1 and t").contains("RuntimeError"));
+}
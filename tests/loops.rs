@@ -0,0 +1,63 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn while_runs_until_false() {
+    assert_eq!(global("i = 0\ni < 5 ?? { i = i + 1 }", "i"), "5");
+}
+
+#[test]
+fn each_goes_over_items_and_indexes() {
+    let program = "total = 0\n@[1, 2, 3] @@ x,i { total = total + x * i }";
+    assert_eq!(global(program, "total"), "8");
+    let program = "out = \"\"\n\"abc\" @@ c { out = c + out }";
+    assert_eq!(global(program, "out"), "cba");
+}
+
+#[test]
+fn break_and_continue() {
+    let program = "
+out = @[]
+@[1, 2, 3, 4] @@ v {
+    v == 2 ? { continue }
+    v == 4 ? { break }
+    out +[] v
+}
+i = 0
+i < 10 ?? {
+    i = i + 1
+    i == 2 ? { continue }
+    i == 4 ? { break }
+    out +[] i * 10
+}
+ok = out == @[1, 3, 10, 30]";
+    assert_eq!(global(program, "ok"), "t");
+}
+
+#[test]
+fn loop_bodies_leave_nothing_on_the_stack() {
+    let program = "
+h = .{
+    @[1, 2] @@ v { v }
+    i = 0
+    i < 2 ?? { i = i + 1 i }
+    @[1, 2, 3] @@ v { v v == 2 ? { break } }
+    @[1, 2, 3] @@ v { v v == 2 ? { continue } }
+    7
+}
+r = h.;";
+    assert_eq!(global(program, "r"), "7");
+}
+
+#[test]
+fn loop_gives_no_value() {
+    assert_eq!(error("x = @[1, 2] @@ v { v }"), "RuntimeError(StackEmpty)");
+}
+
+#[test]
+fn loop_control_outside_loop_is_rejected() {
+    assert_eq!(error("break"), "ParserError(LoopControlOutsideLoop)");
+}
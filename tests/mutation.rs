@@ -0,0 +1,56 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn push_adds_to_the_end_or_before_an_index() {
+    assert_eq!(global("a = @[1, 2]\na +[] 3", "a"), "123");
+    assert_eq!(global("a = @[1, 2]\na +[0] 0", "a"), "012");
+    assert_eq!(global("a = @[1, 2]\na +[-1] 9", "a"), "192");
+}
+
+#[test]
+fn pop_takes_the_last_item_or_the_one_at_an_index() {
+    let program = "a = @[1, 2, 3]\nlast = a -[]\nfirst = a -[0]";
+    assert_eq!(global(program, "last"), "3");
+    assert_eq!(global(program, "first"), "1");
+    assert_eq!(global(program, "a"), "2");
+}
+
+#[test]
+fn removing_a_slice_gives_back_its_items() {
+    let program = "a = @[1, 2, 3, 4]\ntaken = a -[1:3]";
+    assert_eq!(global(program, "taken"), "23");
+    assert_eq!(global(program, "a"), "14");
+    assert_eq!(global("a = @[1, 2]\ntaken = a -[:]\nn = @? a", "n"), "0");
+}
+
+#[test]
+fn extend_adds_the_items_of_another_array() {
+    assert_eq!(global("a = @[1]\na ++ @[2, 3]", "a"), "123");
+}
+
+#[test]
+fn strings_take_chars_and_strings() {
+    assert_eq!(global("s = \"ac\"\ns +[1] \"b\"", "s"), "abc");
+    assert_eq!(global("s = \"abc\"\nc = s -[]", "c"), "c");
+    assert_eq!(global("s = \"abc\"\nc = s -[]", "s"), "ab");
+}
+
+#[test]
+fn changes_are_seen_through_every_reference() {
+    assert_eq!(global("a = @[]\nb = a\nb +[] 1\nn = @? a", "n"), "1");
+}
+
+#[test]
+fn popping_an_empty_array_is_an_error() {
+    assert!(error("a = @[]\nx = a -[]").contains("RuntimeError"));
+}
+
+#[test]
+fn an_index_past_the_end_is_an_error() {
+    assert!(error("a = @[1]\na +[5] 2").contains("RuntimeError"));
+    assert!(error("a = @[1]\nx = a -[5]").contains("RuntimeError"));
+}
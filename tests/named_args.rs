@@ -0,0 +1,48 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+const GREET: &str = "
+greet = .name,greeting: \"hello\",mark: \"!\" {
+    greeting + \" \" + name + mark
+}";
+
+fn greet(call: &str) -> String {
+    global(&format!("{}\nx = {}", GREET, call), "x")
+}
+
+#[test]
+fn defaults_fill_args_left_off() {
+    assert_eq!(greet("greet.\"bob\";"), "hello bob!");
+    assert_eq!(greet("greet.\"bob\",\"hi\";"), "hi bob!");
+}
+
+#[test]
+fn args_can_be_given_by_name() {
+    assert_eq!(greet("greet.\"bob\",mark: \"?\";"), "hello bob?");
+    assert_eq!(greet("greet.mark: \"?\",name: \"amy\";"), "hello amy?");
+}
+
+#[test]
+fn defaults_are_run_each_call_and_see_earlier_args() {
+    let program = "
+calls = 0
+next = .{ global calls = calls + 1\ncalls }
+pair = .a,b: a + next.; { @[a, b] }
+x = pair.10;
+y = pair.10;";
+    assert_eq!(global(program, "x"), "1011");
+    assert_eq!(global(program, "y"), "1012");
+}
+
+#[test]
+fn unknown_names_are_an_error() {
+    assert!(error(&format!("{}\nx = greet.\"bob\",colour: 1;", GREET)).contains("UnknownArgument"));
+}
+
+#[test]
+fn args_without_a_default_must_be_given() {
+    assert!(error(&format!("{}\nx = greet.mark: \"?\";", GREET)).contains("RuntimeError"));
+}
@@ -0,0 +1,37 @@
+extern crate kscript;
+
+mod common;
+
+use common::global;
+
+#[test]
+fn minus_with_nothing_before_it_negates() {
+    assert_eq!(global("x = -5", "x"), "-5");
+    assert_eq!(global("y = 5\nx = -y", "x"), "-5");
+    assert_eq!(global("x = -2.5", "x"), "-2.5");
+}
+
+#[test]
+fn minus_after_an_operator_negates() {
+    assert_eq!(global("y = -5\nx = 3 - -y", "x"), "-2");
+    assert_eq!(global("x = 2 * -3", "x"), "-6");
+    assert_eq!(global("x = @[-1, -2]", "x"), "-1-2");
+}
+
+#[test]
+fn powers_are_done_before_negation() {
+    assert_eq!(global("x = -2 ** 2", "x"), "-4");
+    assert_eq!(global("x = (-2) ** 2", "x"), "4");
+}
+
+#[test]
+fn negating_a_call_or_group() {
+    assert_eq!(global("one = .{ 1 }\nx = -one.;", "x"), "-1");
+    assert_eq!(global("x = -(1 + 2)", "x"), "-3");
+}
+
+#[test]
+fn negating_the_smallest_integer_grows_it() {
+    assert_eq!(global("x = -(-9223372036854775807 - 1)", "x"), "9223372036854775808");
+    assert_eq!(global("x = -9223372036854775808", "x"), "-9223372036854775808");
+}
@@ -0,0 +1,49 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// the lines written by the repl without its prompts
+fn run_repl(input: &str) -> Vec<String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kscript"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("could not start kscript");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.trim_start_matches("& ").to_string())
+        .collect()
+}
+
+#[test]
+fn repl_keeps_globals_between_lines() {
+    let lines = run_repl("a = 2\nb = a * 3\nb >> 1\n");
+    assert!(lines.contains(&"6".to_string()), "{:?}", lines);
+}
+
+#[test]
+fn repl_error_does_not_reuse_global_slots() {
+    let lines = run_repl("g = .{ later }\n1/0\nzz = 5\nlater = 10\nzz >> 1\n");
+    assert!(lines.contains(&"RuntimeError(DivisionByZero)".to_string()), "{:?}", lines);
+    assert!(lines.contains(&"5".to_string()), "{:?}", lines);
+    assert!(!lines.contains(&"10".to_string()), "{:?}", lines);
+}
+
+#[test]
+fn repl_recovers_from_parse_errors() {
+    let lines = run_repl("x = 1\ny = @[\nx >> 1\n");
+    assert!(lines.ends_with(&["1".to_string(), "Exiting".to_string()]), "{:?}", lines);
+}
+
+#[test]
+fn repl_recovers_from_errors_in_functions() {
+    let lines = run_repl("h = .{ 1/0 }\nh.;\nq = 3\nq >> 1\n");
+    assert!(lines.contains(&"3".to_string()), "{:?}", lines);
+}
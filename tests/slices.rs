@@ -0,0 +1,54 @@
+extern crate kscript;
+
+mod common;
+
+use common::{error, global};
+
+#[test]
+fn negative_indexes_count_from_the_end() {
+    assert_eq!(global("a = @[1, 2, 3]\nx = a[-1]", "x"), "3");
+    assert_eq!(global("s = \"abc\"\nx = s[-3]", "x"), "a");
+}
+
+#[test]
+fn slices_take_from_start_up_to_end() {
+    assert_eq!(global("s = \"hello\"\nx = s[1:3]", "x"), "el");
+    assert_eq!(global("s = \"hello\"\nx = s[:-1]", "x"), "hell");
+    assert_eq!(global("a = @[1, 2, 3, 4]\nx = a[2:]", "x"), "34");
+}
+
+#[test]
+fn slice_ends_past_the_collection_are_cut_to_it() {
+    assert_eq!(global("a = @[1, 2, 3]\nx = a[-10:10]", "x"), "123");
+    assert_eq!(global("a = @[1, 2, 3]\nx = a[5:]", "x"), "");
+}
+
+#[test]
+fn slices_step_over_items() {
+    assert_eq!(global("a = @[1, 2, 3, 4, 5]\nx = a[::2]", "x"), "135");
+    assert_eq!(global("s = \"hello\"\nx = s[::-1]", "x"), "olleh");
+    assert_eq!(global("s = \"hello\"\nx = s[3:0:-1]", "x"), "lle");
+}
+
+#[test]
+fn a_step_of_zero_is_an_error() {
+    assert!(error("a = @[1, 2]\nx = a[::0]").contains("SliceStepZero"));
+}
+
+#[test]
+fn steps_past_the_largest_integer_end_the_slice() {
+    assert_eq!(global("a = @[1, 2, 3]\nx = a[1::9223372036854775807]", "x"), "2");
+    assert_eq!(global("a = @[1, 2, 3]\nx = a[1::-9223372036854775808]", "x"), "2");
+}
+
+#[test]
+fn slice_assign_swaps_the_items() {
+    assert_eq!(global("a = @[1, 2, 3, 4]\na =[1:3] @[9]", "a"), "194");
+    assert_eq!(global("s = \"hello\"\ns =[0:1] \"j\"", "s"), "jello");
+    assert_eq!(global("a = @[1, 2, 3, 4]\na =[::2] @[7, 8]", "a"), "7284");
+}
+
+#[test]
+fn stepped_slice_assign_needs_an_item_for_each_index() {
+    assert!(error("a = @[1, 2, 3, 4]\na =[::2] @[7]").contains("RuntimeError"));
+}
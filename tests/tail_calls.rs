@@ -0,0 +1,112 @@
+extern crate kscript;
+
+mod common;
+
+use common::global;
+use kscript::lang::Kscript;
+
+// runs the program with only a few calls allowed at once
+fn run_limited(program: &str) -> Result<Kscript, String> {
+    let mut kscript = Kscript::new();
+    kscript.set_call_limit(4);
+    match kscript.run_string(&format!("{}\n", program)) {
+        Ok(_) => Ok(kscript),
+        Err(error) => Err(format!("{:?}", error)),
+    }
+}
+
+#[test]
+fn self_calls_in_return_position_do_not_grow_the_stack() {
+    let program = "
+count = .n,total {
+    n == 0 ? { total ;; }
+    ..n - 1,total + n
+}
+x = count.1000000,0;";
+    assert_eq!(global(program, "x"), "500000500000");
+}
+
+#[test]
+fn calls_to_other_functions_in_return_position_do_not_grow_the_stack() {
+    let program = "
+is_even = .n {
+    n == 0 ? { t ;; }
+    is_odd.n - 1;
+}
+is_odd = .n {
+    n == 0 ? { f ;; }
+    is_even.n - 1;
+}
+x = is_even.200000;";
+    assert_eq!(global(program, "x"), "t");
+}
+
+#[test]
+fn calls_that_are_not_last_still_return() {
+    let program = "
+sum = .n {
+    n == 0 ? { 0 ;; }
+    n + sum.n - 1;
+}
+x = sum.100;";
+    assert_eq!(global(program, "x"), "5050");
+}
+
+#[test]
+fn calls_inside_try_are_normal_calls() {
+    let program = "
+down = .n {
+    n == 0 ? { throw \"bottom\" }
+    try { down.n - 1; } catch e { n }
+}
+x = down.3;";
+    assert_eq!(global(program, "x"), "1");
+}
+
+#[test]
+fn tail_calls_reuse_the_running_call() {
+    let program = "
+count = .n,total {
+    n == 0 ? { total ;; }
+    ..n - 1,total + n
+}
+x = count.10000,0;";
+    let kscript = run_limited(program).unwrap();
+    assert_eq!(kscript.get_global("x").unwrap().to_string(), "50005000");
+    let program = "
+is_even = .n {
+    n == 0 ? { t ;; }
+    is_odd.n - 1;
+}
+is_odd = .n {
+    n == 0 ? { f ;; }
+    is_even.n - 1;
+}
+x = is_even.1001;";
+    let kscript = run_limited(program).unwrap();
+    assert_eq!(kscript.get_global("x").unwrap().to_string(), "f");
+}
+
+#[test]
+fn calls_that_are_not_last_count_against_the_limit() {
+    let program = "
+sum = .n {
+    n == 0 ? { 0 ;; }
+    n + sum.n - 1;
+}
+x = sum.3;";
+    let kscript = run_limited(program).unwrap();
+    assert_eq!(kscript.get_global("x").unwrap().to_string(), "6");
+    let error = run_limited("sum = .n {\nn == 0 ? { 0 ;; }\nn + sum.n - 1;\n}\nx = sum.10;").unwrap_err();
+    assert!(error.contains("CallLimit(4)"));
+}
+
+#[test]
+fn the_call_limit_can_be_caught() {
+    let program = "
+deep = .n { n + deep.n + 1; }
+kind = \"none\"
+try { deep.0; } catch e { kind = e[\"kind\"] }";
+    let kscript = run_limited(program).unwrap();
+    assert_eq!(kscript.get_global("kind").unwrap().to_string(), "CallLimit");
+}
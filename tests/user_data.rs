@@ -0,0 +1,79 @@
+extern crate kscript;
+
+use std::cell::RefCell;
+use kscript::lang::{DataType, IntoKscript, Kscript, NativeFunction, SharedUserData, UserData};
+
+fn counter() -> UserData {
+    let mut counter = UserData::new("counter", RefCell::new(0i64));
+    counter.add_method(NativeFunction::from_fn("add", &["self", "by"], |this: SharedUserData, by: i64| {
+        let count = this.downcast_ref::<RefCell<i64>>().unwrap();
+        *count.borrow_mut() += by;
+        *count.borrow()
+    }));
+    counter
+}
+
+fn global(kscript: &Kscript, name: &str) -> String {
+    kscript.get_global(name).unwrap().to_string()
+}
+
+#[test]
+fn scripts_call_methods_on_host_values() {
+    let mut kscript = Kscript::new();
+    kscript.set_global("hits", counter().into_kscript());
+    kscript.run_string("a = hits.[\"add\"] 5;\nb = hits.[\"add\"] by: 2;\n").unwrap();
+    assert_eq!(global(&kscript, "a"), "5");
+    assert_eq!(global(&kscript, "b"), "7");
+}
+
+#[test]
+fn rust_reads_the_value_back() {
+    let mut kscript = Kscript::new();
+    kscript.set_global("hits", counter().into_kscript());
+    kscript.run_string("other = hits\nother.[\"add\"] 3;\n").unwrap();
+    let hits = match kscript.get_global("hits") {
+        Some(DataType::UserData(hits)) => hits,
+        other => panic!("{:?}", other),
+    };
+    assert!(hits.is::<RefCell<i64>>());
+    assert_eq!(*hits.downcast_ref::<RefCell<i64>>().unwrap().borrow(), 3);
+    assert!(hits.downcast_ref::<String>().is_none());
+}
+
+#[test]
+fn type_and_display() {
+    let mut kscript = Kscript::new();
+    let mut named = counter();
+    named.set_display(|this| format!("counter at {}", this.downcast_ref::<RefCell<i64>>().unwrap().borrow()));
+    kscript.set_global("plain", counter().into_kscript());
+    kscript.set_global("named", named.into_kscript());
+    kscript.run_string("t1 = type.plain;\ns1 = str.plain;\ns2 = \"{named}\"\n").unwrap();
+    assert_eq!(global(&kscript, "t1"), "counter");
+    assert_eq!(global(&kscript, "s1"), "counter");
+    assert_eq!(global(&kscript, "s2"), "counter at 0");
+}
+
+#[test]
+fn host_values_are_only_equal_to_themselves() {
+    let mut kscript = Kscript::new();
+    kscript.set_global("a", counter().into_kscript());
+    kscript.set_global("b", counter().into_kscript());
+    kscript.run_string("c = a\nsame = a == c\nother = a == b\n").unwrap();
+    assert_eq!(global(&kscript, "same"), "t");
+    assert_eq!(global(&kscript, "other"), "f");
+}
+
+#[test]
+fn methods_that_are_not_there_are_an_error() {
+    let mut kscript = Kscript::new();
+    kscript.set_global("hits", counter().into_kscript());
+    let error = kscript.run_string("hits.[\"sub\"] 1;\n").unwrap_err();
+    assert!(format!("{:?}", error).contains("UnknownMethod"));
+}
+
+#[test]
+#[should_panic]
+fn methods_need_an_arg_for_the_value() {
+    let mut counter = counter();
+    counter.add_method(NativeFunction::from_fn("reset", &[], || 0i64));
+}